@@ -0,0 +1,161 @@
+use std::f32::consts::PI;
+use std::fs;
+use serde::{Serialize, Deserialize};
+
+use crate::config::{DisplayConfig, FRAME_HZ};
+use crate::errors::ChipError;
+
+/// Sample rate asked to the audio device and used for WAV files
+pub const SAMPLE_RATE: u32 = 44100;
+
+///	Shape of the tone played while the sound timer is non-zero
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Waveform {
+    Square,
+    Sine
+}
+
+///	Generates the tone one sample at a time
+/// The phase is kept between buffers so the wave doesn't click when the device asks for more samples
+#[derive(Debug, Clone)]
+pub struct ToneGenerator {
+    waveform: Waveform,
+    frequency: f32,
+    volume: f32,
+    sample_rate: u32,
+    /// fraction of the current period, from 0 to 1
+    phase: f32
+}
+
+impl ToneGenerator {
+    ///	Creates a generator
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `waveform` - _shape of the wave_
+    /// * `frequency` - _tone frequency in Hz_
+    /// * `volume` - _amplitude from 0 (silence) to 1, clamped_
+    /// * `sample_rate` - _samples per second_
+    pub fn new(waveform: Waveform, frequency: u32, volume: f32, sample_rate: u32) -> Self {
+        ToneGenerator {
+            waveform,
+            frequency: frequency as f32,
+            volume: volume.clamp(0.0, 1.0),
+            sample_rate,
+            phase: 0.0
+        }
+    }
+
+    ///	Creates the generator described by the Display section, silent when muted
+    pub fn from_config(config: &DisplayConfig, sample_rate: u32) -> Self {
+        let volume = if config.mute { 0.0 } else { config.volume };
+        ToneGenerator::new(config.waveform, config.tone_hz, volume, sample_rate)
+    }
+
+    pub fn next_sample(&mut self) -> f32 {
+        let sample = match self.waveform {
+            Waveform::Square => if self.phase < 0.5 { 1.0 } else { -1.0 },
+            Waveform::Sine => (2.0 * PI * self.phase).sin()
+        };
+        self.phase = (self.phase + self.frequency / self.sample_rate as f32) % 1.0;
+        sample * self.volume
+    }
+
+    pub fn fill(&mut self, out: &mut [f32]) {
+        out.iter_mut().for_each(|sample| *sample = self.next_sample());
+    }
+}
+
+///	Headless audio backend, renders the tone frame by frame and writes it as a 16-bit mono WAV file
+#[derive(Debug, Clone)]
+pub struct WavRecorder {
+    tone: ToneGenerator,
+    samples: Vec<i16>
+}
+
+impl WavRecorder {
+    pub fn new(tone: ToneGenerator) -> Self {
+        WavRecorder {
+            tone,
+            samples: Vec::new()
+        }
+    }
+
+    ///	Appends one 60Hz frame of audio
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `beeping` - _plays the tone if true (sound timer non-zero), silence otherwise_
+    pub fn push_frame(&mut self, beeping: bool) {
+        for _ in 0..self.tone.sample_rate / FRAME_HZ {
+            let sample = if beeping { self.tone.next_sample() } else { 0.0 };
+            self.samples.push((sample * i16::MAX as f32) as i16);
+        }
+    }
+
+    pub fn samples(&self) -> &[i16] {
+        &self.samples
+    }
+
+    ///	Encodes the samples pushed so far as a RIFF WAVE file
+    pub fn to_wav(&self) -> Vec<u8> {
+        let sample_rate = self.tone.sample_rate;
+        let data_size = (self.samples.len() * 2) as u32;
+        let mut wav = Vec::with_capacity(44 + data_size as usize);
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16_u32.to_le_bytes());
+        // PCM, one channel
+        wav.extend_from_slice(&1_u16.to_le_bytes());
+        wav.extend_from_slice(&1_u16.to_le_bytes());
+        wav.extend_from_slice(&sample_rate.to_le_bytes());
+        // byte rate and block align, 2 bytes per sample
+        wav.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+        wav.extend_from_slice(&2_u16.to_le_bytes());
+        wav.extend_from_slice(&16_u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_size.to_le_bytes());
+        self.samples.iter().for_each(|sample| wav.extend_from_slice(&sample.to_le_bytes()));
+        wav
+    }
+
+    ///	Writes the samples pushed so far to `file`
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `file` - _path of the WAV file, overwritten if it exists_
+    pub fn save(&self, file: &str) -> Result<(), ChipError> {
+        fs::write(file, self.to_wav()).map_err(|e| ChipError::Io(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ToneGenerator, WavRecorder, Waveform, SAMPLE_RATE};
+
+    #[test]
+    fn tone_and_wav() {
+        // 4 samples per period
+        let mut square = ToneGenerator::new(Waveform::Square, 11025, 0.5, SAMPLE_RATE);
+        let mut out = [0.0; 5];
+        square.fill(&mut out);
+        assert_eq!(out, [0.5, 0.5, -0.5, -0.5, 0.5]);
+        let mut sine = ToneGenerator::new(Waveform::Sine, 11025, 2.0, SAMPLE_RATE);
+        sine.fill(&mut out);
+        assert!(out[1] > 0.99 && out[3] < -0.99 && out[2].abs() < 0.01);
+
+        let mut recorder = WavRecorder::new(ToneGenerator::new(Waveform::Square, 440, 1.0, SAMPLE_RATE));
+        recorder.push_frame(true);
+        recorder.push_frame(false);
+        let frame = (SAMPLE_RATE / 60) as usize;
+        assert_eq!(recorder.samples().len(), 2 * frame);
+        assert_eq!(recorder.samples()[0], i16::MAX);
+        assert!(recorder.samples()[frame..].iter().all(|sample| *sample == 0));
+        let wav = recorder.to_wav();
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(wav.len(), 44 + 4 * frame);
+        assert_eq!(u32::from_le_bytes(wav[40..44].try_into().unwrap()), 4 * frame as u32);
+    }
+}
@@ -0,0 +1,67 @@
+use std::{fs, process};
+
+use chip8::chip8::{Chip8, ProgramType};
+use chip8::audio::{ToneGenerator, WavRecorder, SAMPLE_RATE};
+use chip8::config::{Args, parse_display};
+use chip8::errors::ChipError;
+use chip8::headless::{run, run_with_audio, dump_framebuffer, dump_registers, RunLimit};
+use clap::Parser;
+
+/// Runs a program without opening any window, for CI containers
+/// Exits with 0 if the program ended or the cycle/frame limit was reached, 1 on any fault
+fn main() {
+    let mut chip = Chip8::new();
+    let args = Args::parse();
+    let program = if args.binary { ProgramType::Binary(args.file.as_str()) } else { ProgramType::Main(args.file.as_str()) };
+    if let Err(cause) = chip.load_program(program, None, None) {
+        eprintln!("Error loading program: {}", cause);
+        process::exit(cause.status() as i32);
+    }
+    if let Some(file) = &args.replay {
+        match chip8::replay::Replay::load(file) {
+            Ok(replay) => chip.start_replay(replay),
+            Err(cause) => {
+                eprintln!("Error loading replay: {}", cause);
+                process::exit(cause.status() as i32);
+            }
+        }
+    }
+
+    let limit = match (args.cycles, args.frames) {
+        (Some(cycles), _) => RunLimit::Cycles(cycles),
+        (None, Some(frames)) => RunLimit::Frames(frames),
+        (None, None) => RunLimit::UntilExit
+    };
+    let mut recorder = args.wav.as_ref().map(|_| WavRecorder::new(ToneGenerator::from_config(&parse_display(), SAMPLE_RATE)));
+    let result = match recorder.as_mut() {
+        Some(recorder) => run_with_audio(&mut chip, limit, recorder),
+        None => run(&mut chip, limit)
+    };
+    let status = match result {
+        Ok(()) | Err(ChipError::NormalExit { .. }) => 0,
+        Err(fault) => {
+            eprintln!("Program terminated with status: {}, {}", fault.status(), fault);
+            fault.status()
+        }
+    };
+
+    if let (Some(file), Some(recorder)) = (&args.wav, &recorder) {
+        if let Err(cause) = recorder.save(file) {
+            eprintln!("Error writing audio: {}", cause);
+            process::exit(1);
+        }
+    }
+
+    println!("{}", dump_registers(&chip));
+    let framebuffer = dump_framebuffer(&chip, args.dump);
+    match &args.dump_file {
+        Some(file) => {
+            if let Err(cause) = fs::write(file, framebuffer) {
+                eprintln!("Error writing framebuffer: {}", cause);
+                process::exit(1);
+            }
+        },
+        None => print!("{}", framebuffer)
+    }
+    process::exit(status as i32);
+}
@@ -10,12 +10,20 @@ use std::thread::JoinHandle;
 
 use once_cell::sync::Lazy;
 use serde::{Serialize, Deserialize};
+use rand::{Rng, SeedableRng, rngs::StdRng};
 
 use crate::config::parse_chip;
-use crate::operations_set::operations_table::Executable;
-use crate::operations_set::operations_table::{OperationTab, OperationSpecs, Ret, Cls};
+use crate::keypad::Keypad;
+use crate::interrupts::{InterruptController, Interrupt, DELAY_TIMER_IRQ, SOUND_TIMER_IRQ};
+use crate::quirks::Quirks;
+use crate::errors::ChipError;
+use crate::protection::{MemoryRegion, MemoryAccess};
+use crate::operations_set::instruction::{decode, Instruction};
 use crate::timers::Signals;
-use crate::timers::TimerThread;
+use crate::timers::{TimerThread, TimerMode, TimerState};
+use crate::snapshot::{Snapshot, SNAPSHOT_VERSION};
+use crate::replay::{Replay, KeyEvent};
+use crate::framebuffer::Framebuffer;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ChipConfig {
@@ -26,25 +34,163 @@ pub struct ChipConfig {
     pub rti_default_addr: u16,
     pub display_height: usize,
     pub display_width: usize,
-    pub eop_opt_code: u16
+    pub eop_opt_code: u16,
+    pub font_addr: u16,
+    pub memory_size: usize,
+    /// enables the DRWH (9xyn) and DRVH (Exyn) sprite instructions
+    pub extended_sprites: bool,
+    pub timer_mode: TimerMode,
+    /// cycles executed per 60Hz frame, timers tick once per frame in `TimerMode::Cycles`
+    pub instructions_per_frame: u32,
+    pub flag_mode: FlagMode,
+    /// seed of the generator behind RND, a random one is picked when not set
+    pub rng_seed: Option<u64>,
+    pub quirks: Quirks,
+    /// regions programs can't freely write or execute
+    pub protected_regions: Vec<MemoryRegion>,
+    /// ignores `protected_regions`, for ROMs relying on self-modification
+    pub permissive_memory: bool
 }
 
-#[derive(Debug)]
-pub struct EopError {
-    pub status: u8,
-    pub message: String
+/// How VF is written by arithmetic, shift and draw operations
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum FlagMode {
+    /// each flag owns one bit of VF, see the README for the partitioning
+    Partitioned,
+    /// VF is set to exactly 0 or 1 by the last flag-setting operation
+    Standard
+}
+
+/// Flags an operation may report through VF
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VfFlag {
+    Collision,
+    Overflow,
+    NotBorrow,
+    ShiftedOutLsb,
+    ShiftedOutMsb
+}
+
+impl VfFlag {
+    /// Bit of VF owned by the flag in `FlagMode::Partitioned`
+    fn mask(&self) -> u8 {
+        match self {
+            VfFlag::Collision => 0x01,
+            VfFlag::Overflow => 0x02,
+            VfFlag::NotBorrow => 0x04,
+            VfFlag::ShiftedOutLsb => 0x08,
+            VfFlag::ShiftedOutMsb => 0x10
+        }
+    }
+}
+
+/// Size in bytes of each built-in hexadecimal sprite
+pub const FONT_SPRITE_SIZE: u16 = 5;
+
+/// Built-in sprites for the hexadecimal digits 0 - F
+const FONT_SPRITES: [u8; 16 * FONT_SPRITE_SIZE as usize] = [
+    0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
+    0x20, 0x60, 0x20, 0x20, 0x70, // 1
+    0xF0, 0x10, 0xF0, 0x80, 0xF0, // 2
+    0xF0, 0x10, 0xF0, 0x10, 0xF0, // 3
+    0x90, 0x90, 0xF0, 0x10, 0x10, // 4
+    0xF0, 0x80, 0xF0, 0x10, 0xF0, // 5
+    0xF0, 0x80, 0xF0, 0x90, 0xF0, // 6
+    0xF0, 0x10, 0x20, 0x40, 0x40, // 7
+    0xF0, 0x90, 0xF0, 0x90, 0xF0, // 8
+    0xF0, 0x90, 0xF0, 0x10, 0xF0, // 9
+    0xF0, 0x90, 0xF0, 0x90, 0x90, // A
+    0xE0, 0x90, 0xE0, 0x90, 0xE0, // B
+    0xF0, 0x80, 0x80, 0x80, 0xF0, // C
+    0xE0, 0x90, 0x90, 0x90, 0xE0, // D
+    0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
+    0xF0, 0x80, 0xF0, 0x80, 0x80  // F
+];
+
+/// Size in bytes of each built-in SUPER-CHIP large hexadecimal sprite
+pub const BIG_FONT_SPRITE_SIZE: u16 = 10;
+
+/// Built-in 8x10 SUPER-CHIP sprites for the hexadecimal digits 0 - F, placed right after `FONT_SPRITES`
+const BIG_FONT_SPRITES: [u8; 16 * BIG_FONT_SPRITE_SIZE as usize] = [
+    0xFF, 0xFF, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, // 0
+    0x18, 0x78, 0x78, 0x18, 0x18, 0x18, 0x18, 0x18, 0xFF, 0xFF, // 1
+    0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, // 2
+    0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // 3
+    0xC3, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0x03, 0x03, 0x03, 0x03, // 4
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // 5
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, // 6
+    0xFF, 0xFF, 0x03, 0x03, 0x06, 0x0C, 0x18, 0x18, 0x18, 0x18, // 7
+    0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, // 8
+    0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // 9
+    0x7E, 0xFF, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xC3, // A
+    0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, // B
+    0x3C, 0xFF, 0xC3, 0xC0, 0xC0, 0xC0, 0xC0, 0xC3, 0xFF, 0x3C, // C
+    0xFC, 0xFE, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFE, 0xFC, // D
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, // E
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xC0, 0xC0  // F
+];
+
+/// Number of SUPER-CHIP RPL user flags
+pub const RPL_FLAGS: usize = 8;
+
+/// Number of XO-CHIP bitplanes
+pub const GFX_PLANES: usize = 2;
+
+/// Size in bytes of the XO-CHIP audio pattern buffer
+pub const AUDIO_PATTERN_SIZE: usize = 16;
+
+/// Largest memory addressable through a 16-bit I register (XO-CHIP)
+pub const MAX_MEMORY_SIZE: usize = 0x10000;
+
+impl ChipConfig {
+    ///	Checks the built-in font region doesn't overlap the memory mapped registers or the stack, nor runs past the end of memory
+    pub fn font_region_collides(&self) -> bool {
+        let font_addr = self.font_addr as usize;
+        let font_end = font_addr + FONT_SPRITES.len() + BIG_FONT_SPRITES.len();
+        let overlaps = |start: u16, end: u16| font_addr < end as usize && (start as usize) < font_end;
+        font_end > self.memory_size
+        || overlaps(self.first_register_addr, self.first_register_addr + 16)
+        || overlaps(self.stack_init_addr, self.stack_canary)
+    }
+
+    ///	Number of return addresses the stack holds, two bytes each from `stack_init_addr` up to `stack_canary`
+    pub fn stack_depth(&self) -> usize {
+        (self.stack_canary.saturating_sub(self.stack_init_addr) / 2) as usize
+    }
+
+    ///	Checks the stack region can be used, returns the reason when it can't
+    pub fn stack_region_error(&self) -> Option<String> {
+        let registers_end = self.first_register_addr + 16;
+        if !self.stack_init_addr.is_multiple_of(2) || !self.stack_canary.is_multiple_of(2) {
+            Some(format!("stack at {:#06x} and canary at {:#06x} must be 16-bit aligned", self.stack_init_addr, self.stack_canary))
+        }
+        else if self.stack_depth() == 0 || self.stack_depth() > u8::MAX as usize {
+            Some(format!("stack depth {} must be between 1 and {}", self.stack_depth(), u8::MAX))
+        }
+        else if self.stack_canary as usize > self.memory_size {
+            Some(format!("stack canary {:#06x} is outside memory", self.stack_canary))
+        }
+        else if self.stack_init_addr < registers_end && self.first_register_addr < self.stack_canary {
+            Some(format!("stack at {:#06x} overlaps registers", self.stack_init_addr))
+        }
+        else {
+            None
+        }
+    }
 }
 
 /// Choose type of program to be loaded
 pub enum ProgramType<'a> {
     /// name of file, uses default address 0x0200
     Main(&'a str),
+    /// name of a binary .ch8 ROM, copied as is to `program_init`
+    Binary(&'a str),
     /// code and address to load a sub routine
     Routine((&'a String, Option<u16>))
 }
 
 /// Purpose of the routine to be set
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub enum RoutinePurpose {
     DelayTimer,
     SoundTimer,
@@ -52,16 +198,28 @@ pub enum RoutinePurpose {
 }
 
 /// Params to store for each custom routine
-#[derive(Debug, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RoutineParams {
     addr: Option<u16>,
-    purpose: RoutinePurpose
+    purpose: RoutinePurpose,
+    /// interrupt priority of timer routines, see `RoutinePurpose::default_priority` when not declared
+    priority: Option<u8>
+}
+
+impl RoutinePurpose {
+    ///	Priority of timer routines not declaring one, the sound routine can interrupt the delay one
+    pub fn default_priority(&self) -> u8 {
+        match self {
+            RoutinePurpose::SoundTimer => 1,
+            _ => 0
+        }
+    }
 }
 
 /// Represents the chip8 emulator status
 #[derive(Debug)]
 pub struct Chip8 {
-    /// main memory of the chip of 4KiB
+    /// main memory of the chip, 4KiB unless configured otherwise (64KiB for XO-CHIP)
     memory: Vec<u8>,
     /// list of memory mapped registers
     registers: Vec<u16>, 
@@ -73,13 +231,50 @@ pub struct Chip8 {
     pc: u16,
     /// stack pointer, it indexes the `stack` field, next stack item is at sp+1
     sp: u8,
-    /// if set, it holds the timer itself and a mpsc channel to send signals
-    delay_timer: Option<(Arc<Mutex<TimerThread>>, Sender<Signals>)>,
-    sound_timer: Option<(Arc<Mutex<TimerThread>>, Sender<Signals>)>,
-    /// graphics
-    gfx: Vec<Vec<u8>>,
+    /// if set, it holds the timer itself and a mpsc channel to send signals (only threaded timers have one)
+    delay_timer: Option<(Arc<Mutex<TimerThread>>, Option<Sender<Signals>>)>,
+    sound_timer: Option<(Arc<Mutex<TimerThread>>, Option<Sender<Signals>>)>,
+    /// cycles executed since the last frame, only used by `TimerMode::Cycles`
+    frame_cycles: u32,
+    /// graphics, sized from the configured display and doubled in high resolution
+    gfx: Framebuffer,
+    /// XO-CHIP second bitplane, same layout as `gfx`
+    gfx_second_plane: Framebuffer,
+    /// XO-CHIP bitmask of the planes drawing operations act on, bit 0 is `gfx`
+    selected_planes: u8,
+    /// SUPER-CHIP high resolution mode, doubles the configured display size
+    high_res: bool,
+    /// SUPER-CHIP RPL user flags
+    rpl_flags: [u8; RPL_FLAGS],
+    /// XO-CHIP 1-bit audio pattern played while the sound timer is on
+    audio_pattern: [u8; AUDIO_PATTERN_SIZE],
+    /// XO-CHIP pitch register, the pattern plays at 4000*2^((pitch-64)/48) bits per second
+    pitch: u8,
     /// list of user-defined routines
     routines: Vec<RoutineParams>,
+    /// pending timer interrupts and the routines handling them
+    interrupts: InterruptController,
+    /// state of the hexadecimal keypad
+    keypad: Keypad,
+    /// an Fx0A is waiting for a key to be pressed and released
+    waiting_key: bool,
+    /// cycles executed since the chip was created, key events are timed with it
+    cycles: u64,
+    /// seed `rng` was created with, stored in replays
+    rng_seed: u64,
+    /// generator behind RND
+    rng: StdRng,
+    /// key events recorded so far, if recording
+    recording: Option<Replay>,
+    /// replay being played and the index of its next event, live key presses are ignored meanwhile
+    playback: Option<(Replay, usize)>,
+    /// instructions already decoded, indexed by their address, cleared whenever that memory is written
+    /// a hit skips fetching and decoding, the end of program opcode is never stored
+    decoded: Vec<Option<Instruction>>,
+    /// set when gfx changed and the display should be refreshed
+    draw_flag: bool,
+    /// fault raised in the middle of an instruction, reported by `execute_cycle` once it finishes
+    fault: Option<ChipError>,
     /// chip configuration constants
     config: ChipConfig
 }
@@ -87,38 +282,148 @@ pub struct Chip8 {
 impl Chip8 {
     pub fn new() -> Self {
         let config = parse_chip();
-        println!("{:?}", config);
-        let registers: Vec<u16> = {
-            let mut inter = Vec::new();
-            for i in 0..16 {
-               inter.push(config.first_register_addr + (i as u16));
-            }
-            inter
-        };
-
-        let stack: Vec<u16> = {
-            let mut inter = Vec::new();
-            for i in 0..16 {
-               inter.push(config.stack_init_addr + (2*i as u16));
-            }
-            inter
-        };
-        
-        Chip8 {
-            memory: [].to_vec(),
-            registers,
-            stack,
+        let rng_seed = config.rng_seed.unwrap_or_else(rand::random);
+        let mut chip = Chip8 {
+            memory: vec![0_u8; config.memory_size],
+            registers: Chip8::register_addresses(&config),
+            stack: Chip8::stack_addresses(&config),
             i_register: 0x000,
             pc: config.program_init,
             sp: 0x00, // access by STACK_INIT_ADDR + sp*2 in memory or stack[sp]
             delay_timer: None,
             sound_timer: None,
-            gfx: vec![vec![0_u8; 8]; 32],
+            frame_cycles: 0,
+            gfx: Framebuffer::new(config.display_width, config.display_height),
+            gfx_second_plane: Framebuffer::new(config.display_width, config.display_height),
+            selected_planes: 0x01,
+            high_res: false,
+            rpl_flags: [0; RPL_FLAGS],
+            audio_pattern: [0; AUDIO_PATTERN_SIZE],
+            pitch: 64,
             routines: Vec::new(),
+            interrupts: InterruptController::new(),
+            keypad: Keypad::new(),
+            waiting_key: false,
+            cycles: 0,
+            rng_seed,
+            rng: StdRng::seed_from_u64(rng_seed),
+            recording: None,
+            playback: None,
+            decoded: vec![None; config.memory_size],
+            draw_flag: false,
+            fault: None,
             config
+        };
+        // a bad font region is reported by `load_program`, copying the sprites could run past the end of memory
+        if !chip.config.font_region_collides() {
+            chip.load_font_sprites();
+        }
+        chip
+    }
+
+    fn register_addresses(config: &ChipConfig) -> Vec<u16> {
+        (0..16).map(|i| config.first_register_addr + i).collect()
+    }
+
+    fn stack_addresses(config: &ChipConfig) -> Vec<u16> {
+        (0..config.stack_depth() as u16).map(|i| config.stack_init_addr + 2*i).collect()
+    }
+
+    ///	Captures the whole state of the chip, timers included
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            memory: self.memory.clone(),
+            i_register: self.i_register,
+            pc: self.pc,
+            sp: self.sp,
+            delay_timer: self.delay_timer.as_ref().map(|(t, _)| t.lock().state()),
+            sound_timer: self.sound_timer.as_ref().map(|(t, _)| t.lock().state()),
+            frame_cycles: self.frame_cycles,
+            gfx: self.gfx.clone(),
+            gfx_second_plane: self.gfx_second_plane.clone(),
+            selected_planes: self.selected_planes,
+            high_res: self.high_res,
+            rpl_flags: self.rpl_flags,
+            audio_pattern: self.audio_pattern,
+            pitch: self.pitch,
+            routines: self.routines.clone(),
+            interrupts: self.interrupts.clone(),
+            config: self.config.clone()
         }
     }
 
+    ///	Replaces the state of the chip with `snapshot`, timers are relaunched as its config says
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `snapshot` - _state taken by `snapshot`, possibly from another run_
+    pub fn restore(&mut self, snapshot: Snapshot) -> Result<(), ChipError> {
+        // nothing is changed if the state is rejected
+        snapshot.validate()?;
+        Chip8::kill_timer(self.delay_timer.take());
+        Chip8::kill_timer(self.sound_timer.take());
+        self.registers = Chip8::register_addresses(&snapshot.config);
+        self.stack = Chip8::stack_addresses(&snapshot.config);
+        self.config = snapshot.config;
+        self.memory = snapshot.memory;
+        self.flush_decoded();
+        self.i_register = snapshot.i_register;
+        self.pc = snapshot.pc;
+        self.sp = snapshot.sp;
+        self.delay_timer = snapshot.delay_timer.map(|state| self.timer_from_state(state));
+        self.sound_timer = snapshot.sound_timer.map(|state| self.timer_from_state(state));
+        self.frame_cycles = snapshot.frame_cycles;
+        self.gfx = snapshot.gfx;
+        self.gfx_second_plane = snapshot.gfx_second_plane;
+        self.selected_planes = snapshot.selected_planes;
+        self.high_res = snapshot.high_res;
+        self.rpl_flags = snapshot.rpl_flags;
+        self.audio_pattern = snapshot.audio_pattern;
+        self.pitch = snapshot.pitch;
+        self.routines = snapshot.routines;
+        self.interrupts = snapshot.interrupts;
+        self.fault = None;
+        self.waiting_key = false;
+        self.set_draw_flag();
+        Ok(())
+    }
+
+    ///	Switches the timers to `mode`, the running ones keep their count, pause state and routine
+    fn set_timer_mode(&mut self, mode: TimerMode) {
+        if self.config.timer_mode == mode {
+            return;
+        }
+        let delay = self.delay_timer.take().map(|timer| {
+            let state = timer.0.lock().state();
+            Chip8::kill_timer(Some(timer));
+            state
+        });
+        let sound = self.sound_timer.take().map(|timer| {
+            let state = timer.0.lock().state();
+            Chip8::kill_timer(Some(timer));
+            state
+        });
+        self.config.timer_mode = mode;
+        self.delay_timer = delay.map(|state| self.timer_from_state(state));
+        self.sound_timer = sound.map(|state| self.timer_from_state(state));
+    }
+
+    fn timer_from_state(&self, state: TimerState) -> (Arc<Mutex<TimerThread>>, Option<Sender<Signals>>) {
+        let timer = self.new_timer(state.count, state.rti);
+        timer.0.lock().paused = state.paused;
+        timer
+    }
+
+    /// Copies the built-in hexadecimal sprites to `font_addr` in the interpreter memory region
+    fn load_font_sprites(&mut self) {
+        let font_addr = self.config.font_addr as usize;
+        let big_font_addr = font_addr + FONT_SPRITES.len();
+        self.memory[font_addr..big_font_addr].copy_from_slice(&FONT_SPRITES);
+        self.memory[big_font_addr..big_font_addr + BIG_FONT_SPRITES.len()].copy_from_slice(&BIG_FONT_SPRITES);
+        self.flush_decoded();
+    }
+
     ///	Set `source` register to `value` 
     ///
     ///	# _Arguments_
@@ -126,7 +431,7 @@ impl Chip8 {
     /// * `source` - _register to be written_
     /// * `value` - _value to be stored_
     pub fn set_register_value(&mut self, source: u8, value: u8) {
-        self.memory[self.registers[source as usize] as usize] = value;
+        self.write_memory(self.registers[source as usize] as usize, value);
     }
     ///	Retrieve `source` value
     ///
@@ -137,23 +442,319 @@ impl Chip8 {
         self.memory[self.registers[source as usize] as usize]
     }
 
+    ///	Reports `flag` through VF as configured by `flag_mode`
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `flag` - _flag to report_
+    /// * `value` - _whether the flag is raised_
+    pub fn set_vf_flag(&mut self, flag: VfFlag, value: bool) {
+        let vf = match self.config.flag_mode {
+            FlagMode::Partitioned if value => self.get_register_value(15) | flag.mask(),
+            FlagMode::Partitioned => self.get_register_value(15) & !flag.mask(),
+            FlagMode::Standard => value as u8
+        };
+        self.set_register_value(15, vf);
+    }
+
+    pub fn get_config(&self) -> &ChipConfig {
+        &self.config
+    }
+
+    pub fn get_quirks(&self) -> &Quirks {
+        &self.config.quirks
+    }
+
+    /// Signals the display needs to be refreshed
+    pub fn set_draw_flag(&mut self) {
+        self.draw_flag = true;
+    }
+
+    /// Returns whether the display needs to be refreshed and puts the draw flag down
+    pub fn take_draw_flag(&mut self) -> bool {
+        std::mem::replace(&mut self.draw_flag, false)
+    }
+
     pub fn set_i_register_value(&mut self, value: u16) {
         self.i_register = value;
     }
 
-    pub fn set_memory_value(&mut self, value: u8) {
-        self.memory[self.i_register as usize] = value;
+    pub fn get_i_register_value(&self) -> u16 {
+        self.i_register
+    }
+
+    ///	Write `value` to memory at address I-register + `offset`
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `offset` - _offset to add to I-register value_
+    /// * `value` - _value to be stored_
+    pub fn set_memory_value(&mut self, offset: usize, value: u8) {
+        let access = self.i_address(offset).and_then(|addr| self.check_access(addr, MemoryAccess::Write).map(|_| addr));
+        match access {
+            Ok(addr) => self.write_memory(addr, value),
+            // the write is dropped
+            Err(fault) => self.raise_fault(fault)
+        }
+    }
+
+    ///	Returns I-register + `offset`, an `AddressOutOfBounds` fault if it is past the end of memory
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `offset` - _offset to add to I-register value_
+    fn i_address(&self, offset: usize) -> Result<usize, ChipError> {
+        let addr = self.i_register as usize + offset;
+        if addr < self.memory.len() {
+            Ok(addr)
+        }
+        else {
+            Err(ChipError::AddressOutOfBounds { pc: self.pc, addr: addr.min(u16::MAX as usize) as u16 })
+        }
+    }
+
+    ///	Records a fault raised in the middle of an instruction, `execute_cycle` reports it once the instruction finishes
+    /// Only the first fault of the instruction is kept
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `fault` - _fault to report_
+    pub fn raise_fault(&mut self, fault: ChipError) {
+        self.fault.get_or_insert(fault);
+    }
+
+    ///	Checks a program is allowed to access `addr`, the interpreter itself is never checked
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `addr` - _accessed address_
+    /// * `access` - _kind of access_
+    fn check_access(&self, addr: usize, access: MemoryAccess) -> Result<(), ChipError> {
+        if !self.config.permissive_memory && self.config.protected_regions.iter().any(|region| region.forbids(addr, access)) {
+            Err(ChipError::ProtectionFault { pc: self.pc, addr: addr as u16, access })
+        }
+        else {
+            Ok(())
+        }
+    }
+
+    ///	Writes `value` at `addr` dropping the decoded instructions that byte belongs to
+    fn write_memory(&mut self, addr: usize, value: u8) {
+        self.memory[addr] = value;
+        self.decoded[addr] = None;
+        if addr > 0 {
+            self.decoded[addr - 1] = None;
+        }
+    }
+
+    ///	Drops every decoded instruction, for writes that replace whole memory regions
+    fn flush_decoded(&mut self) {
+        // restoring a state may change the memory size
+        self.decoded.resize(self.memory.len(), None);
+        self.decoded.fill(None);
+    }
+
+    ///	Returns the address of the built-in sprite for hexadecimal `digit`
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `digit` - _digit to look up, only the 4 LSB are used_
+    pub fn get_font_sprite_addr(&self, digit: u8) -> u16 {
+        self.config.font_addr + (digit & 0x0F) as u16 * FONT_SPRITE_SIZE
+    }
+
+    ///	Returns the address of the built-in SUPER-CHIP large sprite for hexadecimal `digit`
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `digit` - _digit to look up, only the 4 LSB are used_
+    pub fn get_big_font_sprite_addr(&self, digit: u8) -> u16 {
+        self.config.font_addr + FONT_SPRITES.len() as u16 + (digit & 0x0F) as u16 * BIG_FONT_SPRITE_SIZE
+    }
+
+    pub fn get_rpl_flag(&self, flag: usize) -> u8 {
+        self.rpl_flags[flag]
+    }
+
+    pub fn set_rpl_flag(&mut self, flag: usize, value: u8) {
+        self.rpl_flags[flag] = value;
+    }
+
+    ///	Copies the 16 bytes at I into the XO-CHIP audio pattern buffer
+    pub fn load_audio_pattern(&mut self) {
+        for i in 0..AUDIO_PATTERN_SIZE {
+            self.audio_pattern[i] = self.load_i_address_value(i);
+        }
+    }
+
+    pub fn get_audio_pattern(&self) -> &[u8; AUDIO_PATTERN_SIZE] {
+        &self.audio_pattern
+    }
+
+    pub fn set_pitch(&mut self, pitch: u8) {
+        self.pitch = pitch;
+    }
+
+    pub fn get_pitch(&self) -> u8 {
+        self.pitch
+    }
+
+    ///	Returns the big-endian 16-bit word at `addr`
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `addr` - _address of the most significant byte_
+    pub fn get_memory_word(&self, addr: u16) -> u16 {
+        (self.memory[addr as usize] as u16) << 8 | self.memory[addr as usize + 1] as u16
+    }
+
+    ///	Returns the big-endian 16-bit word at `addr`, an `AddressOutOfBounds` fault if it doesn't fit in memory
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `addr` - _address of the most significant byte_
+    pub fn read_memory_word(&self, addr: usize) -> Result<u16, ChipError> {
+        if addr + 1 < self.memory.len() {
+            Ok(self.get_memory_word(addr as u16))
+        }
+        else {
+            Err(ChipError::AddressOutOfBounds { pc: self.pc, addr: addr.min(u16::MAX as usize) as u16 })
+        }
     }
 
-    ///	Handles the logic for leaving a subroutine
-    pub fn leave_subroutine(&mut self) {
+    pub fn get_pc(&self) -> u16 {
+        self.pc
+    }
+
+    ///	Handles the logic for leaving a subroutine, returning with an empty stack is a fault
+    pub fn leave_subroutine(&mut self) -> Result<(), ChipError> {
+        if self.sp == 0 {
+            return Err(ChipError::StackUnderflow { pc: self.pc });
+        }
         self.sp -= 1;
-        self.pc = (self.memory[self.stack[self.sp as usize] as usize] as u16) << 8 | self.memory[self.stack[self.sp as usize+1] as usize] as u16;
+        self.pc = self.get_memory_word(self.stack[self.sp as usize]);
+        self.interrupts.returned(self.sp);
+        Ok(())
+    }
+
+    pub fn get_sp(&self) -> u8 {
+        self.sp
+    }
+
+    ///	Return addresses currently on the stack, the innermost call last
+    pub fn get_stack(&self) -> Vec<u16> {
+        self.stack[..self.sp as usize].iter().map(|addr| self.get_memory_word(*addr)).collect()
+    }
+
+    pub fn get_stack_depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    ///	Holds the timer interrupts in `sources` pending until they are unmasked
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `sources` - _bitmask of `DELAY_TIMER_IRQ` and `SOUND_TIMER_IRQ`_
+    pub fn mask_interrupts(&mut self, sources: u8) {
+        self.interrupts.mask(sources);
     }
 
-    /// Zeroes out the display
+    ///	Lets the timer interrupts in `sources` be dispatched again
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `sources` - _bitmask of `DELAY_TIMER_IRQ` and `SOUND_TIMER_IRQ`_
+    pub fn unmask_interrupts(&mut self, sources: u8) {
+        self.interrupts.unmask(sources);
+    }
+
+    ///	Freezes or restarts the timers in `timers`, a paused timer keeps its count and raises no interrupt
+    /// Setting a timer again starts it running
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `timers` - _bitmask of `DELAY_TIMER_IRQ` and `SOUND_TIMER_IRQ`_
+    /// * `paused` - _whether to pause or resume them_
+    pub fn set_timers_paused(&mut self, timers: u8, paused: bool) {
+        for (bit, timer) in [(DELAY_TIMER_IRQ, &self.delay_timer), (SOUND_TIMER_IRQ, &self.sound_timer)] {
+            if let (true, Some((t, _))) = (timers & bit != 0, timer) {
+                t.lock().paused = paused;
+            }
+        }
+    }
+
+    /// Zeroes out the selected planes of the display
     pub fn clear_display(&mut self) {
-        self.gfx = vec![vec![0_u8; 8]; 32];
+        for plane in self.get_selected_planes() {
+            self.plane_mut(plane).clear();
+        }
+    }
+
+    /// Returns the current (width, height) of the display in pixels
+    pub fn get_display_size(&self) -> (usize, usize) {
+        if self.high_res {
+            (self.config.display_width * 2, self.config.display_height * 2)
+        } else {
+            (self.config.display_width, self.config.display_height)
+        }
+    }
+
+    ///	Switches between the configured resolution and SUPER-CHIP high resolution, every plane is cleared
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `high_res` - _whether to use the doubled resolution_
+    pub fn set_high_res(&mut self, high_res: bool) {
+        self.high_res = high_res;
+        let (width, height) = self.get_display_size();
+        for plane in 0..GFX_PLANES {
+            *self.plane_mut(plane) = Framebuffer::new(width, height);
+        }
+    }
+
+    ///	Selects the XO-CHIP planes drawing, clearing and scrolling act on
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `planes` - _bitmask of planes, bit 0 is the first plane_
+    pub fn select_planes(&mut self, planes: u8) {
+        self.selected_planes = planes & 0x03;
+    }
+
+    /// Returns the indexes of the selected planes in ascending order
+    pub fn get_selected_planes(&self) -> Vec<usize> {
+        (0..GFX_PLANES).filter(|plane| self.selected_planes & (1 << plane) != 0).collect()
+    }
+
+    fn plane(&self, plane: usize) -> &Framebuffer {
+        if plane == 0 { &self.gfx } else { &self.gfx_second_plane }
+    }
+
+    fn plane_mut(&mut self, plane: usize) -> &mut Framebuffer {
+        if plane == 0 { &mut self.gfx } else { &mut self.gfx_second_plane }
+    }
+
+    ///	Scrolls the selected planes down `rows` pixels, rows entering from the top are blank
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `rows` - _pixels to scroll_
+    pub fn scroll_display_down(&mut self, rows: usize) {
+        for plane in self.get_selected_planes() {
+            self.plane_mut(plane).scroll_down(rows);
+        }
+    }
+
+    ///	Scrolls the selected planes horizontally `columns` pixels, right if positive and left if negative
+    /// Columns entering from the edge are blank
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `columns` - _pixels to scroll_
+    pub fn scroll_display_horizontal(&mut self, columns: i32) {
+        for plane in self.get_selected_planes() {
+            self.plane_mut(plane).scroll_horizontal(columns);
+        }
     }
     
     ///	Modify pc's value, if `increment_or_set` is None, hop to next instruction (pc+2)
@@ -168,6 +769,14 @@ impl Chip8 {
         }
     }
 
+    ///	Hops over the instruction following the current one, XO-CHIP F000 NNNN is 4 bytes long
+    /// The current instruction still has to be skipped with `update_pc`
+    pub fn skip_next_instruction(&mut self) {
+        // skipping past the end of memory is reported when the next instruction is fetched
+        let next_opcode = self.read_memory_word(self.pc as usize + 2).unwrap_or(0);
+        self.pc += if next_opcode == 0xF000 { 4 } else { 2 };
+    }
+
     ///	Send `signal` to either sound or delay timer specified by `target`
     ///
     ///	# _Arguments_
@@ -177,19 +786,23 @@ impl Chip8 {
     pub fn send_signal(&self, sig: Signals, target: &str) -> Result<(), String>{
         match target {
             "sound" => {
-                if let Some((_, sx)) = &self.sound_timer {
-                    sx.send(sig).unwrap();
-                    Ok(())
-                } else {
-                    Err("Sound timer is not set".to_string())
+                match &self.sound_timer {
+                    Some((_, Some(sx))) => {
+                        sx.send(sig).unwrap();
+                        Ok(())
+                    },
+                    Some((_, None)) => Err("Sound timer is not threaded".to_string()),
+                    None => Err("Sound timer is not set".to_string())
                 }
             },
             "delay" => {
-                if let Some((_, sx)) = &self.delay_timer {
-                    sx.send(sig).unwrap();
-                    Ok(())
-                } else {
-                    Err("Sound timer is not set".to_string())
+                match &self.delay_timer {
+                    Some((_, Some(sx))) => {
+                        sx.send(sig).unwrap();
+                        Ok(())
+                    },
+                    Some((_, None)) => Err("Delay timer is not threaded".to_string()),
+                    None => Err("Delay timer is not set".to_string())
                 }
             },
             _ => Err(format!("specified timer doesn't exist: {:?}", target))
@@ -201,8 +814,147 @@ impl Chip8 {
     ///	# _Arguments_
     ///
     /// * `offset` - _offset to add to I-register value_
-    pub fn load_i_address_value(&self, offset: usize) -> u8 {
-        self.memory[self.i_register as usize + offset]
+    pub fn load_i_address_value(&mut self, offset: usize) -> u8 {
+        match self.i_address(offset) {
+            Ok(addr) => self.memory[addr],
+            // reads past the end of memory give 0 until the fault stops the program
+            Err(fault) => {
+                self.raise_fault(fault);
+                0
+            }
+        }
+    }
+
+    ///	Marks `key` as pressed in the keypad
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `key` - _key to press (0x0 - 0xF)_
+    pub fn press_key(&mut self, key: u8) {
+        self.input_key(key, true);
+    }
+
+    ///	Marks `key` as released in the keypad
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `key` - _key to release (0x0 - 0xF)_
+    pub fn release_key(&mut self, key: u8) {
+        self.input_key(key, false);
+    }
+
+    ///	Applies a live key state change, recording it if needed, ignored while a replay is played
+    fn input_key(&mut self, key: u8, pressed: bool) {
+        if self.playback.is_some() {
+            return;
+        }
+        if let Some(replay) = &mut self.recording {
+            replay.events.push(KeyEvent { cycle: self.cycles, key, pressed });
+        }
+        if pressed {
+            self.keypad.press(key);
+        } else {
+            self.keypad.release(key);
+        }
+    }
+
+    ///	Starts recording key state changes, along with the seed of the random number generator
+    /// The generator is reseeded and the timers switched to `TimerMode::Cycles`, so the session can be reproduced
+    /// even if it was recorded after some RND or after restoring a state
+    pub fn start_recording(&mut self) {
+        self.set_rng_seed(self.rng_seed);
+        self.set_timer_mode(TimerMode::Cycles);
+        self.recording = Some(Replay::new(self.rng_seed, self.cycles));
+    }
+
+    ///	Stops recording and returns what was recorded, None if not recording
+    pub fn stop_recording(&mut self) -> Option<Replay> {
+        self.recording.take()
+    }
+
+    ///	Plays `replay` back: the generator is reseeded, the timers use the recorded mode and its key events replace live input
+    /// The cycle counter is set to the cycle the recording started at, the chip must be in the state it was in back then
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `replay` - _replay to play_
+    pub fn start_replay(&mut self, replay: Replay) {
+        self.set_rng_seed(replay.seed);
+        self.set_timer_mode(replay.timer_mode);
+        self.cycles = replay.start_cycle;
+        self.playback = Some((replay, 0));
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    ///	Applies the replay events due by the current cycle, the replay ends after its last event
+    fn play_key_events(&mut self) {
+        if let Some((replay, next)) = &mut self.playback {
+            while let Some(event) = replay.events.get(*next).filter(|event| event.cycle <= self.cycles) {
+                if event.pressed {
+                    self.keypad.press(event.key);
+                } else {
+                    self.keypad.release(event.key);
+                }
+                *next += 1;
+            }
+            if *next == replay.events.len() {
+                self.playback = None;
+            }
+        }
+    }
+
+    pub fn get_cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    pub fn get_rng_seed(&self) -> u64 {
+        self.rng_seed
+    }
+
+    ///	Restarts the generator behind RND from `seed`
+    pub fn set_rng_seed(&mut self, seed: u64) {
+        self.rng_seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    ///	Next byte of the generator behind RND
+    pub fn random_byte(&mut self) -> u8 {
+        self.rng.gen()
+    }
+
+    pub fn is_key_pressed(&self, key: u8) -> bool {
+        self.keypad.is_pressed(key)
+    }
+
+    /// Returns the lowest key currently held down, if any
+    pub fn get_pressed_key(&self) -> Option<u8> {
+        self.keypad.first_pressed()
+    }
+
+    ///	Waits for a key to be pressed and released, as the COSMAC VIP does for Fx0A
+    /// The first call starts the wait, keys held before it have to be released and pressed again
+    /// Returns the key once it is released, None while still waiting
+    pub fn wait_key_release(&mut self) -> Option<u8> {
+        if !self.waiting_key {
+            self.waiting_key = true;
+            self.keypad.start_wait();
+        }
+        let key = self.keypad.take_released();
+        if key.is_some() {
+            self.waiting_key = false;
+        }
+        key
+    }
+
+    ///	Returns the interrupt priority declared by the first routine for `purpose`, its default priority otherwise
+    pub fn get_routine_priority(&self, purpose: RoutinePurpose) -> u8 {
+        match self.routines.iter().find(|rout| rout.purpose == purpose) {
+            Some(RoutineParams { priority: Some(priority), .. }) => *priority,
+            _ => purpose.default_priority()
+        }
     }
 
     ///	Returns the address of the first user-defined routine found in `self.routines`
@@ -223,96 +975,182 @@ impl Chip8 {
             None
         }
     }
-    pub fn get_gfx<'a>(&'a self) -> &'a [Vec<u8>] {
-        self.gfx.as_slice()
+    pub fn get_gfx(&self) -> &Framebuffer {
+        &self.gfx
     }
 
-    ///	Returns a sprite found in `self.gfx` from `coords` and `offset` specified
-    /// It takes care of cyclic representation of the sprite
+    ///	Returns the XO-CHIP bitplane `plane`, 0 is the same as `get_gfx`
     ///
     ///	# _Arguments_
     ///
-    /// * `coords` - _pixel-based coordinates_
+    /// * `plane` - _plane index (0 or 1)_
+    pub fn get_gfx_plane(&self, plane: usize) -> &Framebuffer {
+        self.plane(plane)
+    }
+
+    ///	Returns the 8 pixels of the first plane found from `coords` and `offset` specified, the MSB is the leftmost pixel
+    /// It takes care of cyclic representation of the sprite, in both directions
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `coords` - _pixel-based coordinates (x, y)_
     /// * `offset` - _vertical offset_
-    pub fn get_gfx_sprite(&self, coords: (u8, u8), offset: usize) -> u64 {
-        let target_row = (coords.0 as usize + offset) % self.config.display_height;
-        u64::from_be_bytes(self.gfx[target_row].clone().try_into().unwrap())
+    pub fn get_gfx_sprite(&self, coords: (u8, u8), offset: usize) -> u8 {
+        let (width, height) = (self.gfx.width(), self.gfx.height());
+        let target_row = (coords.1 as usize + offset) % height;
+        (0..8).fold(0_u8, |sprite, i| sprite << 1 | self.gfx.get_pixel((coords.0 as usize + i) % width, target_row) as u8)
     }
 
-    ///	Sets a sprite to `sprite` at `coords` + vertical `offset`
+    ///	XORs `sprite` into the first plane at `coords` + vertical `offset`
+    /// Returns true if any pixel was erased (collision)
     ///
     ///	# _Arguments_
     ///
-    /// * `coords` - _pixel-based coordinates_
+    /// * `coords` - _pixel-based coordinates (x, y)_
     /// * `offset` - _vertical offset_
     /// * `sprite` - _sprite to store_
-    pub fn set_gfx_sprite(&mut self, coords: (u8, u8), offset: usize, sprite: u8) {
-        let target_row = (coords.0 as usize + offset) % self.config.display_height;
-        let _target_col = coords.1 as usize % self.config.display_width;
-        let prev_sprite = self.get_gfx_sprite(coords, offset);
-        let xor_sprite = (sprite as u64).rotate_right(8+coords.1 as u32) ^ prev_sprite;
+    pub fn set_gfx_sprite(&mut self, coords: (u8, u8), offset: usize, sprite: u8) -> bool {
+        self.set_gfx_plane_sprite(0, coords, offset, sprite as u16, 8)
+    }
 
-        // check collisions
-        if prev_sprite != (prev_sprite & xor_sprite) {
-            self.memory[self.registers[15] as usize] |= 0x01; 
-        }
-        // draw it in gfx
-        for i in 0..8 {
-            self.gfx[target_row][i] = (((0xFF as u64).rotate_right((8*(i+1)) as u32) & xor_sprite) >> (self.config.display_width-((i+1)*8))) as u8;
+    ///	XORs a 16 pixels wide SUPER-CHIP `sprite` row into the first plane at `coords` + vertical `offset`
+    /// Returns true if any pixel was erased (collision)
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `coords` - _pixel-based coordinates (x, y)_
+    /// * `offset` - _vertical offset_
+    /// * `sprite` - _sprite row to store_
+    pub fn set_gfx_wide_sprite(&mut self, coords: (u8, u8), offset: usize, sprite: u16) -> bool {
+        self.set_gfx_plane_sprite(0, coords, offset, sprite, 16)
+    }
+
+    ///	XORs the `sprite_width` LSB of `sprite` into `plane` at `coords` + vertical `offset`
+    /// Returns true if any pixel was erased (collision)
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `plane` - _plane index (0 or 1)_
+    /// * `coords` - _pixel-based coordinates (x, y)_
+    /// * `offset` - _vertical offset_
+    /// * `sprite` - _sprite row to store_
+    /// * `sprite_width` - _pixels in the sprite row (8 or 16)_
+    pub fn set_gfx_plane_sprite(&mut self, plane: usize, coords: (u8, u8), offset: usize, sprite: u16, sprite_width: usize) -> bool {
+        let height = self.plane(plane).height();
+        let clip = self.config.quirks.clip_sprites;
+        // the starting position always wraps, only the part of the sprite past the edges is clipped
+        let start_row = coords.1 as usize % height;
+        // clipped rows are not drawn at all
+        if clip && start_row + offset >= height {
+            return false;
         }
+        // pixels pushed past the right edge are dropped when clipping
+        self.plane_mut(plane).xor_blit(coords.0 as usize, start_row + offset, sprite, sprite_width, clip)
     }
 
     // If the timer is already set and counting, it will ovewrite its value
     pub fn set_delay_timer(&mut self, val: u8, rti: Option<u16>) {
         
-        if let Some((timer, ch)) = self.delay_timer.take() {
-            // kill thread
-            // there is the case when a thread has finished but the chip hasn't executed the next cycle so it won't have run the timer subroutine and the sender will send its message to nobody
-            // That would give the failed to send message error
-            // Hence this condition is required
-            if Arc::strong_count(&timer) == 2 {
-                ch.send(Signals::KILL).expect("Failed to send message to delay timer thread");
-            }
-        }
+        Chip8::kill_timer(self.delay_timer.take());
         let rti_ = match rti {
             Some(addr) => addr,
             None => self.config.rti_default_addr
         };
         
-        self.delay_timer = Some(TimerThread::launch(val, rti_));
+        self.delay_timer = Some(self.new_timer(val, rti_));
     }
 
-    pub fn set_sound_timer(&mut self, val: u8, rti: Option<u16>) {
-        if let Some((timer, ch)) = self.sound_timer.take() {
-            // kill thread
-            if Arc::strong_count(&timer) == 2 {
-                ch.send(Signals::KILL).expect("Failed to send message to delay timer thread");
-            }
+    ///	Returns the time left in the delay timer, 0 if it is not set
+    pub fn get_delay_timer_value(&self) -> u8 {
+        match &self.delay_timer {
+            Some((timer, _)) => timer.lock().timer,
+            None => 0
+        }
+    }
+
+    ///	Returns the time left in the sound timer, 0 if it is not set
+    pub fn get_sound_timer_value(&self) -> u8 {
+        match &self.sound_timer {
+            Some((timer, _)) => timer.lock().timer,
+            None => 0
         }
+    }
+
+    pub fn set_sound_timer(&mut self, val: u8, rti: Option<u16>) {
+        Chip8::kill_timer(self.sound_timer.take());
         let rti_ = match rti {
             Some(addr) => addr,
             None => self.config.rti_default_addr
         };
-        self.sound_timer = Some(TimerThread::launch(val, rti_));
+        self.sound_timer = Some(self.new_timer(val, rti_));
     }
 
-    pub fn address_out_of_bounds(address: u16) -> bool {
-        (0xFE00 & address) == 0
+    ///	Stops the thread of a replaced timer, if it is still running
+    fn kill_timer(timer: Option<(Arc<Mutex<TimerThread>>, Option<Sender<Signals>>)>) {
+        if let Some((timer, ch)) = timer {
+            // there is the case when a thread has finished but the chip hasn't executed the next cycle so it won't have run the timer subroutine and the sender will send its message to nobody
+            // That would give the failed to send message error
+            // Hence this condition is required
+            if let (2, Some(ch)) = (Arc::strong_count(&timer), ch) {
+                ch.send(Signals::KILL).expect("Failed to send message to timer thread");
+            }
+        }
+    }
+
+    ///	Creates a timer counting down as configured by `timer_mode`
+    fn new_timer(&self, val: u8, rti: u16) -> (Arc<Mutex<TimerThread>>, Option<Sender<Signals>>) {
+        match self.config.timer_mode {
+            TimerMode::Threaded => {
+                let (timer, ch) = TimerThread::launch(val, rti);
+                (timer, Some(ch))
+            },
+            TimerMode::Cycles => (TimerThread::paced(val, rti), None)
+        }
+    }
+
+    ///	Counts one executed cycle, the timers tick every `instructions_per_frame` cycles
+    fn tick_frame_cycle(&mut self) {
+        self.frame_cycles += 1;
+        if self.frame_cycles < self.config.instructions_per_frame {
+            return;
+        }
+        self.frame_cycles = 0;
+        for timer in [&self.delay_timer, &self.sound_timer].into_iter().flatten() {
+            timer.0.lock().tick();
+        }
+    }
+
+    ///	Checks `address` falls in program memory: above the interpreter region and within the configured memory size
+    pub fn address_out_of_bounds(&self, address: u16) -> bool {
+        (0xFE00 & address) == 0 || address as usize >= self.memory.len()
     }
     
-    pub fn load_program(&mut self, kind: ProgramType, t_pool: Option<&mut Vec<JoinHandle<()>>>, mut cv: Option<Arc<HashMap<String, Condvar>>>) -> Result<(), String> {
+    pub fn load_program(&mut self, kind: ProgramType, t_pool: Option<&mut Vec<JoinHandle<Result<(), String>>>>, mut cv: Option<Arc<HashMap<String, Condvar>>>) -> Result<(), ChipError> {
         // parse file
         // (fixed, returns error) check there are no weird registers being used (from G-)
         // transform hex addresses to decimal
         static mut GRAPH: Lazy<Arc<Mutex<HashMap<String, Option<u16>>>>> = Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
         static mut MEMORY:  Lazy<Arc<Mutex<Vec<u8>>>> = Lazy::new(|| Arc::new(Mutex::new(vec![0_u8; 4096])));
+        if self.config.font_region_collides() {
+            return Err(ChipError::InvalidConfig(format!("font sprites at {:#06x} overlap registers or stack or don't fit in memory", self.config.font_addr)));
+        }
+        if self.config.memory_size > MAX_MEMORY_SIZE {
+            return Err(ChipError::InvalidConfig(format!("memory size {:#x} exceeds the 16-bit address space", self.config.memory_size)));
+        }
+        if let Some(cause) = self.config.stack_region_error() {
+            return Err(ChipError::InvalidConfig(cause));
+        }
+        if let ProgramType::Binary(file) = kind {
+            return self.load_binary(file);
+        }
         let load_addr: u16;
-        let mut thread_pool: Vec<JoinHandle<()>> = Vec::new();
+        let mut thread_pool: Vec<JoinHandle<Result<(), String>>> = Vec::new();
         let code = match kind {
             ProgramType::Main(file) => {
-                let mut temp = fs::read_to_string(file).expect("I/O Error");
+                let mut temp = fs::read_to_string(file).map_err(|e| ChipError::Io(e.to_string()))?;
                 // preprocess all labels
                 unsafe {
+                    MEMORY.lock().resize(self.config.memory_size, 0);
                     GRAPH = self.preprocess_all_labels(temp.clone());
                     let mut pre_cv = HashMap::new();
                     for key in GRAPH.lock().keys() {
@@ -320,7 +1158,7 @@ impl Chip8 {
                     }
                     cv = Some(Arc::new(pre_cv));
                 }
-                self.hex_2_dec(&mut temp);
+                self.hex_2_dec(&mut temp).map_err(ChipError::Syntax)?;
                 // pass in the graph and the arc memory
                 self.parse_directives(&mut temp, &mut thread_pool, &cv)?;
                 load_addr = self.config.program_init;
@@ -332,7 +1170,8 @@ impl Chip8 {
                     None => self.config.rti_default_addr
                 };
                 text.clone()
-            }
+            },
+            ProgramType::Binary(_) => unreachable!("binary programs are not parsed")
         };
         // launch thread to process labels
         // I should keep the handles for main
@@ -341,6 +1180,8 @@ impl Chip8 {
         let mem_clone = unsafe {Arc::clone(&MEMORY)};
         let handle = thread::spawn(move || {
             let mut inst_buff: Vec<u8> = Vec::new();
+            // the remaining lines are still read after an error, threads may be waiting for the labels they define
+            let mut error: Option<String> = None;
             let cv = cv.unwrap();
             for (line_no, line) in code.lines().enumerate() {
                 // check labels
@@ -352,7 +1193,9 @@ impl Chip8 {
                     parsed_line.drain(..1); // delete column
                     graph_lck.insert(label_name.clone(), Some(load_addr+(line_no as u16)*2));
                     // unblock possible waiting threads
-                    cv.get(&label_name).unwrap().notify_all();
+                    if let Some(label_cv) = cv.get(&label_name) {
+                        label_cv.notify_all();
+                    }
                 }
                 for item in graph_lck.clone().keys() {
                     if let Some(_) = line.find(item) {
@@ -371,30 +1214,58 @@ impl Chip8 {
                     parsed_line = parsed_line.replacen(" ", "", 1);
                 }
                 let inst: Vec<&str> = parsed_line.split(' ').collect();
-                let parsed_inst: u16 = Chip8::parse_instruction(&Chip8::new(), &inst).expect("Error parsing instruction: ");
-                inst_buff.push(((0xFF00 & parsed_inst) >> 8) as u8); // big-endian
-                inst_buff.push((0x00FF & parsed_inst) as u8);
+                match Chip8::parse_instruction(&Chip8::new(), &inst) {
+                    Ok(parsed_inst) => {
+                        inst_buff.push(((0xFF00 & parsed_inst) >> 8) as u8); // big-endian
+                        inst_buff.push((0x00FF & parsed_inst) as u8);
+                    },
+                    Err(cause) => {
+                        error.get_or_insert(format!("line {} `{}`: {}", line_no + 1, line.trim(), cause));
+                    }
+                }
+            }
+            if let Some(cause) = error {
+                return Err(cause);
             }
             let mut mem_lck = mem_clone.lock();
             for (i, inst) in inst_buff.into_iter().enumerate() {
                 //self.memory[(load_addr as usize)+i] = inst;
                 mem_lck[(load_addr as usize)+i] = inst;
             }
+            Ok(())
         });
         
         if let ProgramType::Main(_) = kind {
             // wait for handles
-            for h in thread_pool {
-                h.join().unwrap();
-            }
-            handle.join().unwrap();
+            // every thread is joined before reporting the first error
+            let results: Vec<Result<(), String>> = thread_pool.into_iter().chain(std::iter::once(handle))
+                .map(|h| h.join().unwrap_or_else(|_| Err("assembler thread panicked".to_string())))
+                .collect();
+            results.into_iter().collect::<Result<(), String>>().map_err(ChipError::Syntax)?;
             self.memory = unsafe {MEMORY.lock().clone()};
+            self.load_font_sprites();
         } else {
             t_pool.unwrap().push(handle);
         }
         Ok(())
     }
 
+    ///	Copies a binary ROM straight to `program_init`, no parsing is involved
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `file` - _path to the ROM_
+    fn load_binary(&mut self, file: &str) -> Result<(), ChipError> {
+        let rom = fs::read(file).map_err(|e| ChipError::Io(e.to_string()))?;
+        let load_addr = self.config.program_init as usize;
+        if load_addr + rom.len() > self.memory.len() {
+            return Err(ChipError::RomTooLarge { size: rom.len(), addr: load_addr as u16 });
+        }
+        self.memory[load_addr..load_addr + rom.len()].copy_from_slice(&rom);
+        self.flush_decoded();
+        Ok(())
+    }
+
     fn preprocess_all_labels(&self, mut text: String) -> Lazy<Arc<Mutex<HashMap<String, Option<u16>>>>> {
         let graph: Lazy<Arc<Mutex<HashMap<String, Option<u16>>>>>  = Lazy::new(| | Arc::new(Mutex::new(HashMap::new())));
         let mut graph_lck = graph.lock();
@@ -416,30 +1287,32 @@ impl Chip8 {
         graph
     }
 
-    fn hex_2_dec(&self, text: &mut String) {
+    fn hex_2_dec(&self, text: &mut String) -> Result<(), String> {
         while let Some(at) = text.find("0x") {
             let mut i = 2;
             while at+i+1 < text.len() && "0123456789abcdefABCDEF".contains(text[at+i..at+i+1].chars().collect::<Vec<char>>()[0])  {
                 i += 1;
             }
-            let decimal_from_hex = u64::from_str_radix(&text[at+2..at+i], 16).unwrap();
+            let decimal_from_hex = u64::from_str_radix(&text[at+2..at+i], 16).map_err(|_| format!("Invalid hexadecimal number: {:?}", &text[at..at+i]))?;
             *text = text.replacen(&text[at..at+i], &decimal_from_hex.to_string(), 1);
         }
+        Ok(())
     }
 
-    fn parse_directives(&mut self, text: &mut String, t_pool: &mut Vec<JoinHandle<()>>, cv: &Option<Arc<HashMap<String, Condvar>>>) -> Result<(), String> {
+    fn parse_directives(&mut self, text: &mut String, t_pool: &mut Vec<JoinHandle<Result<(), String>>>, cv: &Option<Arc<HashMap<String, Condvar>>>) -> Result<(), ChipError> {
         while let Some(previous_code_to_directive) = text.find("!") { // find first directive in the code
             let mut routine_params = RoutineParams {
                 addr: None,
-                purpose: RoutinePurpose::Ordinary
+                purpose: RoutinePurpose::Ordinary,
+                priority: None
             };
             //let blank_lines: Vec<(usize, &str)> = text.lines().enumerate().filter(|tup: &(usize, &str)| tup.1=="" && tup.0 > previous_code_to_directive).collect();
-            let blank_lines = text.find("\n\n").unwrap();
+            let blank_lines = text.find("\n\n").ok_or(ChipError::Syntax("Routine is not followed by a blank line".to_string()))?;
             let mut with_directives_code: String = text.drain(previous_code_to_directive..blank_lines).collect();
             while let Some(dir) = with_directives_code.find("!") {
-                let until = with_directives_code.find("\n").unwrap();
+                let until = with_directives_code.find("\n").ok_or(ChipError::Syntax("Routine has no code after its directives".to_string()))?;
                 let dir_line: String = with_directives_code.drain(dir..until).collect();
-                self.parse_specific_directive(&dir_line, &mut routine_params)?;
+                self.parse_specific_directive(&dir_line, &mut routine_params).map_err(ChipError::Syntax)?;
                 // remove trailing \n
                 with_directives_code.drain(0..1);
             }
@@ -457,13 +1330,20 @@ impl Chip8 {
 
     fn parse_specific_directive(&self, directive: &String, params: &mut RoutineParams) -> Result<(), String> {
         let portioned_directive: Vec<&str> = directive.split("=").collect();
+        let value = portioned_directive.get(1).copied().unwrap_or_default();
         match portioned_directive[0] {
             "!place_at" => {
-                params.addr = Some(portioned_directive[1].parse::<u16>().unwrap());
+                params.addr = Some(self.parse_number(value)?);
                 Ok(())
             },
             "!is_subroutine_for" => {
-                match portioned_directive[1] {
+                // an optional priority may follow the timer: !is_subroutine_for=sound,2
+                let (timer, priority) = match value.split_once(',') {
+                    Some((timer, priority)) => (timer, Some(priority.trim().parse::<u8>().map_err(|_| format!("Routine priority is incorrect: {:?}", priority))?)),
+                    None => (value, None)
+                };
+                params.priority = priority;
+                match timer {
                     "delay" => {
                         params.purpose = RoutinePurpose::DelayTimer;
                         Ok(())
@@ -472,7 +1352,7 @@ impl Chip8 {
                         params.purpose = RoutinePurpose::SoundTimer;
                         Ok(())
                     },
-                    _ => Err(format!("Specified routine purpose is incorrect: {:?}", portioned_directive[1]))
+                    _ => Err(format!("Specified routine purpose is incorrect: {:?}", timer))
                 }
             }
             _ => Err(format!("Specified directived is not found: {:?}", portioned_directive[0]))
@@ -482,152 +1362,178 @@ impl Chip8 {
     fn parse_instruction(&self, inst: &[&str]) -> Result<u16, String> {
         
         let clean_reg = if inst.len() > 1 { inst[1].replace(',', "") } else { "".to_string() };
-        match inst[0] {
+        match inst.first().copied().unwrap_or_default() {
             "LD" => {
+                let source = self.operand(inst, 2)?;
                 match clean_reg.as_str() {
                     "I" => {
-                        let addr_mask = 0x0FFF & (inst[2].parse::<u16>().unwrap());
+                        let addr_mask = 0x0FFF & self.parse_number(source)?;
                         // check it is not accessing out of bounds address
-                        if Chip8::address_out_of_bounds(addr_mask) {
+                        if self.address_out_of_bounds(addr_mask) {
                             Err(format!("Address out of bounds: {:#06x}", addr_mask))
                         } else {
                             Ok(0xA000 | addr_mask)
                         }
                     },
-                    "[I]" => {
-                        let (rx, _) = self.parse_common_registers(inst[2], "r1").unwrap();
-                        Ok(0xF055 | (rx << 8))
-                    }
-                    "DT" => {
-                        let vx = inst[2].chars().nth(1).unwrap().to_digit(16).unwrap() as u16;
-                        let vx_mask = 0x0F00 & (vx << 8);
-                        Ok(0xF015 | vx_mask)
-                    },
-                    "ST" => {
-                        let vx = inst[2].chars().nth(1).unwrap().to_digit(16).unwrap() as u16;
-                        let vx_mask = 0x0F00 & (vx << 8);
-                        Ok(0xF018 | vx_mask)
-                    },
+                    "[I]" => Ok(0xF055 | (self.parse_register(source)? << 8)),
+                    "F" => Ok(0xF029 | (self.parse_register(source)? << 8)),
+                    "B" => Ok(0xF033 | (self.parse_register(source)? << 8)),
+                    "HF" => Ok(0xF030 | (self.parse_register(source)? << 8)),
+                    "R" => Ok(0xF075 | (self.parse_register(source)? << 8)),
+                    "DT" => Ok(0xF015 | (self.parse_register(source)? << 8)),
+                    "ST" => Ok(0xF018 | (self.parse_register(source)? << 8)),
                     _ => { // it is a common register
+                        let rx = self.parse_register(&clean_reg)?;
+                        // wait for a key press
+                        // OptF_0A
+                        if source == "K" {
+                            Ok(0xF00A | (rx << 8))
+                        }
+                        // read the delay timer
+                        // OptF_07
+                        else if source == "DT" {
+                            Ok(0xF007 | (rx << 8))
+                        }
+                        // load registers from the RPL user flags
+                        // OptF_85
+                        else if source == "R" {
+                            Ok(0xF085 | (rx << 8))
+                        }
+                        // load registers from memory
+                        // OptF_65
+                        else if source == "[I]" {
+                            Ok(0xF065 | (rx << 8))
+                        }
                         // load from another register
                         // Opt8_0
-                        if inst[2].chars().nth(0).unwrap() == 'V' {
-                            match self.parse_common_registers(&clean_reg, inst[2]) {
-                                Some((regx, regy)) => Ok(0x8000 | (regx << 8) | (regy << 4)),
-                                None => Err(format!("Error parsing instruction: {:?}", inst))
-                            }
+                        else if source.starts_with('V') {
+                            Ok(0x8000 | (rx << 8) | (self.parse_register(source)? << 4))
                         }
                         // load constant value
                         // Opt6
                         else {
-                            let (rx, _) = self.parse_common_registers(&clean_reg, "r1").unwrap();
-                            Ok(0x6000 | (rx << 8) | (0x00FF & inst[2].parse::<u16>().unwrap()) )
+                            Ok(0x6000 | (rx << 8) | (0x00FF & self.parse_number(source)?))
                         }
                     }
                 }
             },
-            "OR" => {
-                match self.parse_common_registers(&clean_reg, inst[2]) {
-                    Some((regx, regy)) => Ok(0x8001 | (regx << 8) | (regy << 4)),
-                    None => Err(format!("Error parsing instruction: {:?}", inst))
-                }
-                
-            },
-            "AND" => {
-                match self.parse_common_registers(&clean_reg, inst[2]) {
-                    Some((regx, regy)) => Ok(0x8002 | (regx << 8) | (regy << 4)),
-                    None => Err(format!("Error parsing instruction: {:?}", inst))
-                }
-            },
-            "XOR" => {
-                match self.parse_common_registers(&clean_reg, inst[2]) {
-                    Some((regx, regy)) => Ok(0x8003 | (regx << 8) | (regy << 4)),
-                    None => Err(format!("Error parsing instruction: {:?}", inst))
-                }
-            },
+            "OR" => self.parse_register_pair(0x8001, &clean_reg, self.operand(inst, 2)?),
+            "AND" => self.parse_register_pair(0x8002, &clean_reg, self.operand(inst, 2)?),
+            "XOR" => self.parse_register_pair(0x8003, &clean_reg, self.operand(inst, 2)?),
             "ADD" => {
+                let source = self.operand(inst, 2)?;
+                // OptF_1E
+                if clean_reg == "I" {
+                    Ok(0xF01E | (self.parse_register(source)? << 8))
+                }
                 // Opt8_4
-                if inst[2].chars().nth(0).unwrap() == 'V' {
-                    match self.parse_common_registers(&clean_reg, inst[2]) {
-                        Some((regx, regy)) => Ok(0x8004 | (regx << 8) | (regy << 4)),
-                        None => Err(format!("Error parsing instruction: {:?}", inst))
-                    }
+                else if source.starts_with('V') {
+                    self.parse_register_pair(0x8004, &clean_reg, source)
                 }
                 // Opt7 
                 else {
-                    let (rx, _) = self.parse_common_registers(&clean_reg, "r1").unwrap();
-                    Ok(0x7000 | (rx << 8) | (0x00FF & inst[2].parse::<u16>().unwrap()))
+                    Ok(0x7000 | (self.parse_register(&clean_reg)? << 8) | (0x00FF & self.parse_number(source)?))
                 }
                 
             },
-            "SUB" => {
-                match self.parse_common_registers(&clean_reg, inst[2]) {
-                    Some((regx, regy)) => Ok(0x8005 | (regx << 8) | (regy << 4)),
-                    None => Err(format!("Error parsing instruction: {:?}", inst))
-                }
-            },
-            "SHR" => {
-                match self.parse_common_registers(&clean_reg, inst[2]) {
-                    Some((regx, regy)) => Ok(0x8006 | (regx << 8) | (regy << 4)),
-                    None => Err(format!("Error parsing instruction: {:?}", inst))
-                }
-            },
-            "SUBN" => {
-                match self.parse_common_registers(&clean_reg, inst[2]) {
-                    Some((regx, regy)) => Ok(0x8007 | (regx << 8) | (regy << 4)),
-                    None => Err(format!("Error parsing instruction: {:?}", inst))
-                }
+            "SUB" => self.parse_register_pair(0x8005, &clean_reg, self.operand(inst, 2)?),
+            // Vy is optional, V0 is used when it is missing
+            "SHR" => self.parse_register_pair(0x8006, &clean_reg, self.operand(inst, 2).unwrap_or("V0")),
+            "SUBN" => self.parse_register_pair(0x8007, &clean_reg, self.operand(inst, 2)?),
+            // Vy is optional, V0 is used when it is missing
+            "SHL" => self.parse_register_pair(0x800E, &clean_reg, self.operand(inst, 2).unwrap_or("V0")),
+            "DRW" => {
+                let rows = 0x000F & self.parse_number(self.operand(inst, 3)?)?;
+                Ok(self.parse_register_pair(0xD000, &clean_reg, self.operand(inst, 2)?)? | rows)
             },
-            "SHL" => {
-                match self.parse_common_registers(&clean_reg, inst[2]) {
-                    Some((regx, regy)) => Ok(0x800E | (regx << 8) | (regy << 4)),
-                    None => Err(format!("Error parsing instruction: {:?}", inst))
+            "DRWH" => {
+                let columns = 0x000F & self.parse_number(self.operand(inst, 3)?)?;
+                // 9xy0 would be SNE
+                if columns == 0 {
+                    return Err(format!("Error parsing instruction: {:?}", inst));
                 }
+                Ok(self.parse_register_pair(0x9000, &clean_reg, self.operand(inst, 2)?)? | columns)
             },
-            "DRW" => {
-                match self.parse_common_registers(&clean_reg, inst[2].replace(',', "").as_str()) {
-                    Some((regx, regy)) => Ok(0xD000 | (regx << 8) | (regy << 4) | (0x000F & inst[3].parse::<u16>().unwrap())),
-                    None => Err(format!("Error parsing instruction: {:?}", inst))
+            "DRVH" => {
+                let regx = self.parse_register(&clean_reg)?;
+                let regy = self.parse_register(self.operand(inst, 2)?)?;
+                let regn = self.parse_register(self.operand(inst, 3)?)?;
+                // Ex9E and ExA1 would be SKP and SKNP
+                if matches!((regy, regn), (0x9, 0xE) | (0xA, 0x1)) {
+                    return Err(format!("Error parsing instruction: {:?}", inst));
                 }
+                Ok(0xE000 | (regx << 8) | (regy << 4) | regn)
             },
             "JP" => {
                 // is OptB
                 if inst.len() > 2 {
-                    Ok(0xB000 | (0x0FFF & inst[2].parse::<u16>().unwrap()))
+                    Ok(0xB000 | (0x0FFF & self.parse_number(self.operand(inst, 2)?)?))
                 }
                 // is Opt1
                 else {
-                    Ok(0x1000 | (0x0FFF & inst[1].parse::<u16>().unwrap()))
+                    Ok(0x1000 | (0x0FFF & self.parse_number(self.operand(inst, 1)?)?))
                 }
             },
-            "CALL" => Ok(0x2000 | (0x0FFF & inst[1].parse::<u16>().unwrap())),
+            "CALL" => Ok(0x2000 | (0x0FFF & self.parse_number(self.operand(inst, 1)?)?)),
             "SE" => {
-                let regs = self.parse_common_registers(&clean_reg, inst[2]).unwrap();
+                let source = self.operand(inst, 2)?;
                 // is Opt5
-                if inst[2].chars().nth(0).unwrap() == 'V' {
-                    Ok(0x5000 | (regs.0 << 8) | (regs.1 << 4))
+                if source.starts_with('V') {
+                    self.parse_register_pair(0x5000, &clean_reg, source)
                 }
                 // is Opt3 
                 else {
-                    Ok(0x3000 | (regs.0 << 8) | (0x00FF & inst[2].parse::<u16>().unwrap()))
+                    Ok(0x3000 | (self.parse_register(&clean_reg)? << 8) | (0x00FF & self.parse_number(source)?))
                 }
             },
             "SNE" => {
+                let source = self.operand(inst, 2)?;
                 // is Opt9
-                if inst[2].chars().nth(0).unwrap() == 'V' {
-                    let (rx, ry) = self.parse_common_registers(&clean_reg, inst[2]).unwrap();
-                    Ok(0x9000 | (rx << 8) | (ry << 4))
+                if source.starts_with('V') {
+                    self.parse_register_pair(0x9000, &clean_reg, source)
                 }
                 // is Opt4 
                 else {
-                    let (rx, _) = self.parse_common_registers(&clean_reg, "r1").unwrap();
-                    Ok(0x4000 | (rx << 8) | (0x00FF & inst[2].parse::<u16>().unwrap()))
+                    Ok(0x4000 | (self.parse_register(&clean_reg)? << 8) | (0x00FF & self.parse_number(source)?))
                 }
             },
-            "RND" => {
-                let (rx, _) = self.parse_common_registers(&clean_reg, "r1").unwrap();
-                Ok(0xC000 | (rx << 8) | (0x00FF & inst[2].parse::<u16>().unwrap()))
-            }
+            "RND" => Ok(0xC000 | (self.parse_register(&clean_reg)? << 8) | (0x00FF & self.parse_number(self.operand(inst, 2)?)?)),
+            "SKP" => Ok(0xE09E | (self.parse_register(&clean_reg)? << 8)),
+            "SKNP" => Ok(0xE0A1 | (self.parse_register(&clean_reg)? << 8)),
+            "SYS" => {
+                let addr = 0x0FFF & self.parse_number(self.operand(inst, 1)?)?;
+                // 00nn holds the special instructions
+                if addr < 0x0100 {
+                    return Err(format!("Error parsing instruction: {:?}", inst));
+                }
+                Ok(addr)
+            },
+            "SCD" => Ok(0x00C0 | (0x000F & self.parse_number(self.operand(inst, 1)?)?)),
+            "SCR" => Ok(0x00FB),
+            "SCL" => Ok(0x00FC),
+            "EXIT" => Ok(0x00FD),
+            "LOW" => Ok(0x00FE),
+            "HIGH" => Ok(0x00FF),
+            "SAVE" => self.parse_register_pair(0x5002, &clean_reg, self.operand(inst, 2)?),
+            "LOAD" => self.parse_register_pair(0x5003, &clean_reg, self.operand(inst, 2)?),
+            "PLANE" => Ok(0xF001 | ((0x000F & self.parse_number(self.operand(inst, 1)?)?) << 8)),
+            "AUDIO" => Ok(0xF002),
+            "PITCH" => Ok(0xF03A | (self.parse_register(&clean_reg)? << 8)),
+            "MASK" | "UNMASK" | "STOP" | "RESUME" => {
+                let sources = match inst.get(1) {
+                    Some(&"DT") => DELAY_TIMER_IRQ,
+                    Some(&"ST") => SOUND_TIMER_IRQ,
+                    None => DELAY_TIMER_IRQ | SOUND_TIMER_IRQ,
+                    _ => return Err(format!("Error parsing instruction: {:?}", inst))
+                };
+                let base: u16 = match inst[0] {
+                    "MASK" => 0x00A0,
+                    "UNMASK" => 0x00B0,
+                    "STOP" => 0x0090,
+                    _ => 0x0080
+                };
+                Ok(base | sources as u16)
+            },
             "RET" => Ok(0x00EE),
             "CLS" => Ok(0x00E0),
             _ => Err("Undefined Instruction".to_string()) // undefined instruction
@@ -635,9 +1541,30 @@ impl Chip8 {
 
     }
 
+    ///	Returns operand `n` of `inst` without its trailing comma, an error if it is missing
+    fn operand<'a>(&self, inst: &[&'a str], n: usize) -> Result<&'a str, String> {
+        inst.get(n).map(|operand| operand.trim_end_matches(',')).ok_or(format!("Missing operand in instruction: {:?}", inst))
+    }
+
+    fn parse_number(&self, value: &str) -> Result<u16, String> {
+        value.parse::<u16>().map_err(|_| format!("Invalid number: {:?}", value))
+    }
+
+    fn parse_register(&self, reg: &str) -> Result<u16, String> {
+        self.parse_common_registers(reg, reg).map(|(rx, _)| rx).ok_or(format!("Invalid register: {:?}", reg))
+    }
+
+    ///	Encodes the xy registers of `opcode`, as in 8xy1 - OR Vx, Vy
+    fn parse_register_pair(&self, opcode: u16, rx: &str, ry: &str) -> Result<u16, String> {
+        match self.parse_common_registers(rx, ry) {
+            Some((regx, regy)) => Ok(opcode | (regx << 8) | (regy << 4)),
+            None => Err(format!("Invalid registers: {:?}, {:?}", rx, ry))
+        }
+    }
+
     fn parse_common_registers(&self, rx: &str, ry: &str) -> Option<(u16,u16)> {
         
-        if let (Some(rx_value), Some(ry_value)) = (rx.chars().nth(1).unwrap().to_digit(16), ry.chars().nth(1).unwrap().to_digit(16))
+        if let (Some(rx_value), Some(ry_value)) = (rx.chars().nth(1)?.to_digit(16), ry.chars().nth(1)?.to_digit(16))
         {
             Some((
                 rx_value as u16, ry_value as u16
@@ -646,91 +1573,75 @@ impl Chip8 {
         else { None }
     }
 
-    pub fn execute_cycle(&mut self) -> Result<(), EopError> {
-        // Fetch next opcode
-
-        let next_opcode: u16 = ((self.memory[(self.pc as usize)] as u16) << 8) | self.memory[(self.pc as usize)+1] as u16;
-
-        // Decode opcode
-        if next_opcode == self.config.eop_opt_code {
-            return Err(EopError {status: 0, message: "".to_string()})
-        }
-        // special operations
-        let operation: Box<dyn Executable>  = match next_opcode {
-            0x00EE => Box::new(Ret { }),
-            0x00E0 => Box::new(Cls { }),
-            _ => OperationTab::fetch_operation((0xF0 & (next_opcode >> 8)) as u8).unwrap()
-        };
-        
-        let nibble: u8 = (0x000F & next_opcode) as u8; // operation code (if has one)
-        let addr: u16 = 0x0FFF & next_opcode;
-        let constant: u8 = (0x00FF & next_opcode) as u8;
-        let rx: u8 = (0x0F & (next_opcode >> 8)) as u8;
-        let ry: u8 = (0x0F & (next_opcode >> 4)) as u8;
-
-        let opt_specs = OperationSpecs {
-            nibble, addr, constant, rx, ry
+    pub fn execute_cycle(&mut self) -> Result<(), ChipError> {
+        self.play_key_events();
+        self.check_access(self.pc as usize, MemoryAccess::Execute)?;
+        // fetching and decoding are skipped for instructions already decoded at this PC
+        let instruction = match self.decoded.get(self.pc as usize).copied().flatten() {
+            Some(instruction) => instruction,
+            None => {
+                // Fetch next opcode
+                // the whole opcode has to fit in memory, the PC can run off the end with jumps or skips
+                let next_opcode: u16 = self.read_memory_word(self.pc as usize)?;
+                if next_opcode == self.config.eop_opt_code {
+                    return Err(ChipError::NormalExit { pc: self.pc })
+                }
+                // Decode opcode
+                let instruction = decode(next_opcode).map_err(|cause| ChipError::IllegalOpcode { pc: self.pc, opcode: cause.opcode })?;
+                self.decoded[self.pc as usize] = Some(instruction);
+                instruction
+            }
         };
+        // 00FD - EXIT (SUPER-CHIP) behaves as the end of program
+        if instruction == Instruction::Exit {
+            return Err(ChipError::NormalExit { pc: self.pc })
+        }
 
         // Execute
 
-            // execute retrieved operation with chip parameters
-        if let Err(cause) = operation.execute(opt_specs, self) {
-            return Err(EopError { status: 1, message: "Error in operation execution: ".to_string() + &cause })
+        instruction.execute(self)?;
+        self.cycles += 1;
+        if let Some(fault) = self.fault.take() {
+            return Err(fault);
         }
 
         // Update timers
         // Perhaps handling these as interruptions would be better
+        if self.config.timer_mode == TimerMode::Cycles {
+            self.tick_frame_cycle();
+        }
 
-        let delay_t_called = match self.delay_timer.take() {
-            Some((timer, ch)) => {
-                let t_left = timer.lock();
-                if t_left.timer == 0 {
-                    // dispatch setter subroutine
-                    if let Err(cause) = self.call_subroutine(t_left.rti){
-                        return Err(EopError { status: 1, message: "Error handling subroutine: ".to_string() + &cause });
-                    }
-                    true
-                } else {
-                    drop(t_left);
-                    self.delay_timer = Some((timer, ch));
-                    false
-                }
-            },
-            None => false
-        };
-
-        match self.sound_timer.take() {
-            // the sound timer is set and delay timer hasn't called its subroutine
-            Some((timer, ch)) => {
-                let t_left = timer.lock();
-                if t_left.timer == 0  && !delay_t_called {
-                    // dispatch setter subroutine
-                    if let Err(cause) = self.call_subroutine(t_left.rti){
-                        return Err(EopError { status: 1, message: "Error handling subroutine: ".to_string() + &cause });
-                    }
-                } else {
-                    drop(t_left);
-                    self.sound_timer = Some((timer, ch))
-                }
-            },
-            _ => { }
-        };
+        // expired timers raise their interrupt, it is dispatched once unmasked and not outranked by a running routine
+        for (source, purpose) in [(DELAY_TIMER_IRQ, RoutinePurpose::DelayTimer), (SOUND_TIMER_IRQ, RoutinePurpose::SoundTimer)] {
+            let timer = if source == DELAY_TIMER_IRQ { &mut self.delay_timer } else { &mut self.sound_timer };
+            let expired = match timer {
+                Some((t, _)) if t.lock().timer == 0 => Some(t.lock().rti),
+                _ => None
+            };
+            if let Some(rti) = expired {
+                *timer = None;
+                let priority = self.get_routine_priority(purpose);
+                self.interrupts.raise(Interrupt { source, rti, priority });
+            }
+        }
+        if let Some(interrupt) = self.interrupts.dispatch(self.sp) {
+            self.call_subroutine(interrupt.rti)?;
+        }
 
         Ok(())
         
     }
 
-    pub fn call_subroutine(&mut self, addr: u16) -> Result<(), String>{
-        // check stack overflow
-        let next_sp = self.config.stack_init_addr + (self.sp as u16)*2;
-        if next_sp == self.config.stack_canary {
-            Err("Reached stack canary: buffer overflow".to_string())
+    pub fn call_subroutine(&mut self, addr: u16) -> Result<(), ChipError>{
+        // check stack overflow, the next slot would be at the canary
+        if self.sp as usize >= self.stack.len() {
+            Err(ChipError::StackOverflow { pc: self.pc })
         }
         else {
             // store pc where sp point to
-            self.memory[next_sp as usize] = ((self.pc >> 8) & 0x00FF) as u8;
-            self.memory[next_sp as usize+1] = (self.pc & 0x00FF) as u8;
+            let next_sp = self.stack[self.sp as usize];
+            self.write_memory(next_sp as usize, ((self.pc >> 8) & 0x00FF) as u8);
+            self.write_memory(next_sp as usize+1, (self.pc & 0x00FF) as u8);
             // increment sp
             self.sp += 1;
 
@@ -752,8 +1663,10 @@ mod tests {
     use super::Chip8;
     mod parsing_tests {
         use std::collections::HashMap;
+        use std::fs;
 
         use crate::chip8::{RoutineParams, RoutinePurpose};
+        use crate::errors::ChipError;
 
         use super::*;
 
@@ -771,8 +1684,9 @@ mod tests {
         fn hex_2_dec_test() {
             let test_chip = Chip8::new();
             let mut test_string = String::from("this is a test\n bla0x10\n blip\n lal0x200a");
-            test_chip.hex_2_dec(&mut test_string);
+            test_chip.hex_2_dec(&mut test_string).unwrap();
             assert_eq!("this is a test\n bla16\n blip\n lal512a", test_string);
+            assert!(test_chip.hex_2_dec(&mut String::from("LD V1, 0xZ\n")).is_err());
         }
 
         #[test]
@@ -798,7 +1712,29 @@ mod tests {
                 vec!["OR", "VR,", "V6"],
                 vec!["DRW", "V1,", "V3,", "13"],
                 vec!["RET"],
-                vec!["CLS"]
+                vec!["CLS"],
+                vec!["SKP", "V4"],
+                vec!["SKNP", "VB"],
+                vec!["LD", "V2,", "K"],
+                vec!["LD", "V3,", "DT"],
+                vec!["ADD", "I,", "V5"],
+                vec!["LD", "F,", "VA"],
+                vec!["LD", "B,", "V1"],
+                vec!["LD", "VE,", "[I]"],
+                vec!["SCD", "4"],
+                vec!["HIGH"],
+                vec!["LD", "HF,", "V2"],
+                vec!["LD", "R,", "V7"],
+                vec!["LD", "V7,", "R"],
+                vec!["SAVE", "V1,", "V4"],
+                vec!["PLANE", "3"],
+                vec!["PITCH", "V9"],
+                vec!["DRWH", "V1,", "V3,", "4"],
+                vec!["DRVH", "V1,", "V3,", "VA"],
+                vec!["MASK", "DT"],
+                vec!["UNMASK"],
+                vec!["STOP", "ST"],
+                vec!["RESUME", "DT"]
             ].into_iter();
             let parsed_inst_1 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
             assert_eq!(Ok(0x8132), parsed_inst_1, "expected 0x8132, found: {:#06x}", parsed_inst_1.clone().unwrap());
@@ -814,18 +1750,85 @@ mod tests {
             assert_eq!(Ok(0x00EE), parsed_inst_6,"expected 0x00EE, found: {:#06x}", parsed_inst_6.clone().unwrap() );
             let parsed_inst_7 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
             assert_eq!(Ok(0x00E0), parsed_inst_7,"expected 0x00E0, found: {:#06x}", parsed_inst_7.clone().unwrap() );
+            let parsed_inst_8 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0xE49E), parsed_inst_8,"expected 0xE49E, found: {:#06x}", parsed_inst_8.clone().unwrap() );
+            let parsed_inst_9 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0xEBA1), parsed_inst_9,"expected 0xEBA1, found: {:#06x}", parsed_inst_9.clone().unwrap() );
+            let parsed_inst_10 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0xF20A), parsed_inst_10,"expected 0xF20A, found: {:#06x}", parsed_inst_10.clone().unwrap() );
+            let parsed_inst_11 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0xF307), parsed_inst_11,"expected 0xF307, found: {:#06x}", parsed_inst_11.clone().unwrap() );
+            let parsed_inst_12 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0xF51E), parsed_inst_12,"expected 0xF51E, found: {:#06x}", parsed_inst_12.clone().unwrap() );
+            let parsed_inst_13 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0xFA29), parsed_inst_13,"expected 0xFA29, found: {:#06x}", parsed_inst_13.clone().unwrap() );
+            let parsed_inst_14 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0xF133), parsed_inst_14,"expected 0xF133, found: {:#06x}", parsed_inst_14.clone().unwrap() );
+            let parsed_inst_15 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0xFE65), parsed_inst_15,"expected 0xFE65, found: {:#06x}", parsed_inst_15.clone().unwrap() );
+            let parsed_inst_16 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0x00C4), parsed_inst_16,"expected 0x00C4, found: {:#06x}", parsed_inst_16.clone().unwrap() );
+            let parsed_inst_17 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0x00FF), parsed_inst_17,"expected 0x00FF, found: {:#06x}", parsed_inst_17.clone().unwrap() );
+            let parsed_inst_18 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0xF230), parsed_inst_18,"expected 0xF230, found: {:#06x}", parsed_inst_18.clone().unwrap() );
+            let parsed_inst_19 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0xF775), parsed_inst_19,"expected 0xF775, found: {:#06x}", parsed_inst_19.clone().unwrap() );
+            let parsed_inst_20 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0xF785), parsed_inst_20,"expected 0xF785, found: {:#06x}", parsed_inst_20.clone().unwrap() );
+            let parsed_inst_21 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0x5142), parsed_inst_21,"expected 0x5142, found: {:#06x}", parsed_inst_21.clone().unwrap() );
+            let parsed_inst_22 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0xF301), parsed_inst_22,"expected 0xF301, found: {:#06x}", parsed_inst_22.clone().unwrap() );
+            let parsed_inst_23 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0xF93A), parsed_inst_23,"expected 0xF93A, found: {:#06x}", parsed_inst_23.clone().unwrap() );
+            let parsed_inst_24 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0x9134), parsed_inst_24,"expected 0x9134, found: {:#06x}", parsed_inst_24.clone().unwrap() );
+            let parsed_inst_25 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0xE13A), parsed_inst_25,"expected 0xE13A, found: {:#06x}", parsed_inst_25.clone().unwrap() );
+            let parsed_inst_26 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0x00A1), parsed_inst_26,"expected 0x00A1, found: {:#06x}", parsed_inst_26.clone().unwrap() );
+            let parsed_inst_27 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0x00B3), parsed_inst_27,"expected 0x00B3, found: {:#06x}", parsed_inst_27.clone().unwrap() );
+            let parsed_inst_28 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0x0092), parsed_inst_28,"expected 0x0092, found: {:#06x}", parsed_inst_28.clone().unwrap() );
+            let parsed_inst_29 = Chip8::parse_instruction(&test_chip, &test_instructions.next().unwrap());
+            assert_eq!(Ok(0x0081), parsed_inst_29,"expected 0x0081, found: {:#06x}", parsed_inst_29.clone().unwrap() );
+        }
+
+        #[test]
+        fn parse_instruction_errors_test() {
+            let test_chip = Chip8::new();
+            // bad numeric operands
+            assert!(test_chip.parse_instruction(&["DRW", "V1,", "V2,", "x"]).is_err());
+            assert!(test_chip.parse_instruction(&["LD", "V1,", "256x"]).is_err());
+            assert!(test_chip.parse_instruction(&["JP", "loop"]).is_err());
+            // DRVH with the registers of SKP
+            assert!(test_chip.parse_instruction(&["DRVH", "V1,", "V9,", "VE"]).is_err());
+            assert_eq!(Ok(0x0300), test_chip.parse_instruction(&["SYS", "768"]));
+            assert!(test_chip.parse_instruction(&["SYS", "224"]).is_err());
+            assert!(test_chip.parse_instruction(&["SE", "V1,", "5"]).is_ok());
+            // missing operands
+            assert!(test_chip.parse_instruction(&["SCD"]).is_err());
+            assert!(test_chip.parse_instruction(&["DRW", "V1,", "V2"]).is_err());
+            assert!(test_chip.parse_instruction(&["LD", "DT,"]).is_err());
+            assert!(test_chip.parse_instruction(&["SKP"]).is_err());
+            assert!(test_chip.parse_instruction(&[""]).is_err());
+            assert!(test_chip.parse_instruction(&[]).is_err());
         }
 
         #[test]
         fn parse_specific_directive_test() {
             let chip = Chip8::new();
-            let mut params = RoutineParams { addr: None, purpose: RoutinePurpose::Ordinary };
+            let mut params = RoutineParams { addr: None, purpose: RoutinePurpose::Ordinary, priority: None };
             chip.parse_specific_directive(&"!place_at=2048".to_string(), &mut params).unwrap();
             assert_eq!(Some(0x0800), params.addr, "Expected addr 0x0800, found  {:#06x}", params.addr.unwrap());
             chip.parse_specific_directive(&"!is_subroutine_for=delay".to_string(), &mut params).unwrap();
             assert_eq!(RoutinePurpose::DelayTimer, params.purpose);
             chip.parse_specific_directive(&"!is_subroutine_for=sound".to_string(), &mut params).unwrap();
             assert_eq!(RoutinePurpose::SoundTimer, params.purpose);
+            chip.parse_specific_directive(&"!is_subroutine_for=delay,2".to_string(), &mut params).unwrap();
+            assert_eq!((RoutinePurpose::DelayTimer, Some(2)), (params.purpose.clone(), params.priority));
             let err_res = chip.parse_specific_directive(&"!badaboo".to_string(), &mut params);
             assert!(matches!(err_res, Err(_)));
         }
@@ -859,6 +1862,31 @@ mod tests {
             ], chip.memory[512..524])
         }
 
+        #[test]
+        fn load_program_syntax_error() {
+            let mut chip = Chip8::new();
+            let file = std::env::temp_dir().join("chip8_syntax_error.txt");
+            fs::write(&file, "LD V1, V2\nFOO V1, V2\n").unwrap();
+            let result = chip.load_program(crate::chip8::ProgramType::Main(file.to_str().unwrap()), None, None);
+            assert!(matches!(result, Err(ChipError::Syntax(ref cause)) if cause.contains("line 2")), "{:?}", result);
+            // the main program waits for a label defined after a bad line of a routine
+            let file = std::env::temp_dir().join("chip8_syntax_error_routine.txt");
+            fs::write(&file, "!is_subroutine_for=delay\nSCD\nback: RET\n\nCALL back\n").unwrap();
+            let result = chip.load_program(crate::chip8::ProgramType::Main(file.to_str().unwrap()), None, None);
+            assert!(matches!(result, Err(ChipError::Syntax(ref cause)) if cause.contains("line 1 `SCD`")), "{:?}", result);
+        }
+
+        #[test]
+        fn load_binary_program() {
+            let mut chip = Chip8::new();
+            chip.load_program(crate::chip8::ProgramType::Binary("../tests/mock_program.ch8"), None, None).unwrap();
+            assert_eq!([
+                0x60, 0x0A,
+                0xA2, 0x06,
+                0xF0, 0x29,
+            ], chip.memory[512..518])
+        }
+
         // WITH DIRECTIVES
         #[test]
         fn load_program_2(){
@@ -921,9 +1949,285 @@ mod tests {
     }
 
     mod execution_tests {
-        use crate::chip8::RoutineParams;
+        use crate::chip8::{RoutineParams, FlagMode};
+        use crate::quirks::{Quirks, QuirksPreset};
+        use crate::timers::TimerMode;
+        use crate::errors::ChipError;
+        use crate::protection::{MemoryRegion, MemoryAccess, Protection};
+        use crate::snapshot::Snapshot;
+        use crate::framebuffer::Framebuffer;
+        use crate::replay::KeyEvent;
+        use crate::interrupts::DELAY_TIMER_IRQ;
 
         use super::*;
+        #[test]
+        fn cycle_timers_test() {
+            let mut chip = Chip8::new();
+            chip.config.timer_mode = TimerMode::Cycles;
+            chip.config.instructions_per_frame = 2;
+            let program_init = chip.config.program_init;
+            // JP program_init
+            chip.memory[program_init as usize] = 0x10 | (program_init >> 8) as u8;
+            chip.memory[program_init as usize + 1] = (program_init & 0x00FF) as u8;
+            chip.set_delay_timer(2, None);
+            for _ in 0..3 {
+                chip.execute_cycle().unwrap();
+            }
+            assert_eq!(chip.get_delay_timer_value(), 1);
+            assert_eq!(chip.pc, program_init);
+            // second frame ends, the timer's subroutine is dispatched
+            chip.execute_cycle().unwrap();
+            assert_eq!(chip.pc, chip.config.rti_default_addr);
+        }
+
+        #[test]
+        fn stop_resume_timer_test() {
+            let mut chip = Chip8::new();
+            chip.config.timer_mode = TimerMode::Cycles;
+            chip.config.instructions_per_frame = 1;
+            let program_init = chip.config.program_init as usize;
+            // STOP DT, RESUME DT, JP to itself
+            let jump = 0x1000 | (program_init as u16 + 4);
+            let program = [0x00, 0x91, 0x00, 0x81, (jump >> 8) as u8, (jump & 0x00FF) as u8];
+            chip.memory[program_init..program_init + program.len()].copy_from_slice(&program);
+            chip.set_delay_timer(2, None);
+            chip.execute_cycle().unwrap();
+            // no tick while stopped
+            assert_eq!(chip.get_delay_timer_value(), 2);
+            chip.execute_cycle().unwrap();
+            assert_eq!(chip.get_delay_timer_value(), 1);
+            chip.execute_cycle().unwrap();
+            assert_eq!(chip.pc, chip.config.rti_default_addr);
+        }
+
+        #[test]
+        fn standard_flags_test() {
+            let mut chip = Chip8::new();
+            chip.config.flag_mode = FlagMode::Standard;
+            let program_init = chip.config.program_init as usize;
+            // SUB V1, V2 then SUBN V3, V4 with equal operands, nothing is borrowed
+            let program = [0x81, 0x25, 0x83, 0x47];
+            chip.memory[program_init..program_init + program.len()].copy_from_slice(&program);
+            chip.set_register_value(0x01, 0x33);
+            chip.set_register_value(0x02, 0x33);
+            chip.set_register_value(0x03, 0x44);
+            chip.set_register_value(0x04, 0x44);
+            chip.execute_cycle().unwrap();
+            assert_eq!((chip.get_register_value(0x01), chip.get_register_value(0x0F)), (0x00, 1));
+            chip.set_register_value(0x0F, 0);
+            chip.execute_cycle().unwrap();
+            assert_eq!((chip.get_register_value(0x03), chip.get_register_value(0x0F)), (0x00, 1));
+        }
+
+        #[test]
+        fn load_store_quirk_test() {
+            let mut chip = Chip8::new();
+            chip.config.quirks = Quirks::from_preset(QuirksPreset::Chip48);
+            let program_init = chip.config.program_init as usize;
+            // LD V2, [I] twice
+            let program = [0xF2, 0x65, 0xF2, 0x65];
+            chip.memory[program_init..program_init + program.len()].copy_from_slice(&program);
+            chip.set_i_register_value(0x0400);
+            chip.execute_cycle().unwrap();
+            // CHIP-48 leaves I on the last register loaded
+            assert_eq!(chip.get_i_register_value(), 0x0402);
+            // COSMAC VIP wraps I around the 16-bit address space instead of overflowing
+            chip.config.quirks = Quirks::from_preset(QuirksPreset::CosmacVip);
+            chip.set_i_register_value(0xFFFE);
+            assert!(matches!(chip.execute_cycle(), Err(ChipError::AddressOutOfBounds { .. })));
+            assert_eq!(chip.get_i_register_value(), 0x0001);
+        }
+
+        #[test]
+        fn self_modifying_code_test() {
+            let mut chip = Chip8::new();
+            let program_init = chip.config.program_init;
+            // LD VA, 5
+            chip.set_i_register_value(program_init);
+            chip.set_memory_value(0, 0x6A);
+            chip.set_memory_value(1, 0x05);
+            chip.execute_cycle().unwrap();
+            assert_eq!(chip.get_register_value(0x0A), 0x05);
+            // the cached LD VA, 5 is replaced by LD VA, 7
+            chip.set_memory_value(1, 0x07);
+            chip.update_pc(Some(program_init));
+            chip.execute_cycle().unwrap();
+            assert_eq!(chip.get_register_value(0x0A), 0x07);
+        }
+
+        #[test]
+        fn execution_errors_test() {
+            let mut chip = Chip8::new();
+            let program_init = chip.config.program_init as usize;
+            // JP 0x0100, an illegal opcode, EXIT
+            let program = [0x11, 0x00, 0x81, 0x28, 0x00, 0xFD];
+            chip.memory[program_init..program_init + program.len()].copy_from_slice(&program);
+            assert_eq!(chip.execute_cycle(), Err(ChipError::AddressOutOfBounds { pc: program_init as u16, addr: 0x0100 }));
+            chip.update_pc(None);
+            assert_eq!(chip.execute_cycle(), Err(ChipError::IllegalOpcode { pc: program_init as u16 + 2, opcode: 0x8128 }));
+            chip.update_pc(None);
+            assert_eq!(chip.execute_cycle().unwrap_err().status(), 0);
+        }
+
+        #[test]
+        fn memory_protection_test() {
+            let mut chip = Chip8::new();
+            let program_init = chip.config.program_init as usize;
+            // LD [I], V0 with I in the interpreter area, then JP 0x0100
+            let program = [0xF0, 0x55, 0x11, 0x00];
+            chip.memory[program_init..program_init + program.len()].copy_from_slice(&program);
+            chip.set_register_value(0x00, 0xAA);
+            chip.set_i_register_value(0x0060);
+            assert_eq!(chip.execute_cycle(), Err(ChipError::ProtectionFault { pc: program_init as u16, addr: 0x0060, access: MemoryAccess::Write }));
+            assert_ne!(chip.memory[0x0060], 0xAA);
+            chip.config.protected_regions.push(MemoryRegion { start: program_init, end: program_init + 2, protection: Protection::ReadOnly });
+            chip.set_i_register_value(program_init as u16);
+            chip.update_pc(Some(program_init as u16));
+            assert!(matches!(chip.execute_cycle(), Err(ChipError::ProtectionFault { .. })));
+            // permissive mode lets ROMs rewrite their own code
+            chip.config.permissive_memory = true;
+            chip.update_pc(Some(program_init as u16));
+            chip.execute_cycle().unwrap();
+            assert_eq!(chip.memory[program_init], 0xAA);
+        }
+
+        #[test]
+        fn memory_bounds_test() {
+            let mut chip = Chip8::new();
+            let program_init = chip.config.program_init as usize;
+            // LD I, 0xFFFF then LD V0, [I] with 4 KiB of memory
+            let program = [0xF0, 0x00, 0xFF, 0xFF, 0xF0, 0x65];
+            chip.memory[program_init..program_init + program.len()].copy_from_slice(&program);
+            chip.execute_cycle().unwrap();
+            assert_eq!(chip.get_i_register_value(), 0xFFFF);
+            assert_eq!(chip.execute_cycle(), Err(ChipError::AddressOutOfBounds { pc: program_init as u16 + 4, addr: 0xFFFF }));
+            // ADD I, V1 past the end then LD B, V1
+            let program = [0xF1, 0x1E, 0xF1, 0x33];
+            chip.memory[program_init..program_init + program.len()].copy_from_slice(&program);
+            chip.flush_decoded();
+            chip.update_pc(Some(program_init as u16));
+            chip.set_i_register_value(0x0FFF);
+            chip.set_register_value(0x01, 0xFF);
+            chip.execute_cycle().unwrap();
+            assert_eq!(chip.execute_cycle(), Err(ChipError::AddressOutOfBounds { pc: program_init as u16 + 2, addr: 0x10FE }));
+            // the operand of LD I, NNNN is past the end of memory
+            let end = chip.memory.len() - 2;
+            chip.memory[end..].copy_from_slice(&[0xF0, 0x00]);
+            chip.update_pc(Some(end as u16));
+            assert_eq!(chip.execute_cycle(), Err(ChipError::AddressOutOfBounds { pc: end as u16, addr: end as u16 + 2 }));
+            // the PC on the last byte or past the end of memory
+            chip.update_pc(Some(end as u16 + 1));
+            assert_eq!(chip.execute_cycle(), Err(ChipError::AddressOutOfBounds { pc: end as u16 + 1, addr: end as u16 + 1 }));
+            chip.update_pc(Some(end as u16 + 2));
+            assert_eq!(chip.execute_cycle(), Err(ChipError::AddressOutOfBounds { pc: end as u16 + 2, addr: end as u16 + 2 }));
+        }
+
+        #[test]
+        fn snapshot_restore_test() {
+            let mut chip = Chip8::new();
+            chip.config.timer_mode = TimerMode::Cycles;
+            chip.set_register_value(0x03, 0x42);
+            chip.set_i_register_value(0x0345);
+            chip.call_subroutine(0x0400).unwrap();
+            chip.set_delay_timer(7, None);
+            chip.set_timers_paused(DELAY_TIMER_IRQ, true);
+            let file = std::env::temp_dir().join("chip8_snapshot_test.state");
+            let file = file.to_str().unwrap();
+            chip.snapshot().save(file).unwrap();
+
+            let mut restored = Chip8::new();
+            restored.restore(Snapshot::load(file).unwrap()).unwrap();
+            assert_eq!(restored.get_register_value(0x03), 0x42);
+            assert_eq!(restored.get_i_register_value(), 0x0345);
+            assert_eq!((restored.pc, restored.get_stack()), (0x0400, vec![0x0200]));
+            assert_eq!(restored.get_delay_timer_value(), 7);
+            assert!(restored.snapshot().delay_timer.unwrap().paused);
+            assert_eq!(restored.config, chip.config);
+
+            let mut outdated = chip.snapshot();
+            outdated.version += 1;
+            assert!(matches!(restored.restore(outdated), Err(ChipError::Snapshot(_))));
+            // corrupt states are rejected before anything is restored
+            let mut corrupt = [chip.snapshot(), chip.snapshot(), chip.snapshot(), chip.snapshot()];
+            corrupt[0].sp = chip.config.stack_depth() as u8 + 1;
+            corrupt[1].pc = chip.config.memory_size as u16 - 1;
+            corrupt[2].config.stack_canary += 1;
+            corrupt[3].gfx = Framebuffer::new(128, 64);
+            restored.set_register_value(0x03, 0x00);
+            for snapshot in corrupt {
+                assert!(matches!(restored.restore(snapshot), Err(ChipError::Snapshot(_))));
+            }
+            assert_eq!(restored.get_register_value(0x03), 0x00);
+        }
+
+        #[test]
+        fn record_and_replay_test() {
+            let program_init = 0x0200;
+            // RND V0, 0xFF then JP to itself
+            let program = [0xC0, 0xFF, 0x12, 0x02];
+            let mut chip = Chip8::new();
+            chip.memory[program_init..program_init + program.len()].copy_from_slice(&program);
+            chip.start_recording();
+            chip.press_key(0x5);
+            chip.execute_cycle().unwrap();
+            let random = chip.get_register_value(0x00);
+            assert_eq!(chip.pc, 0x0202);
+            chip.execute_cycle().unwrap();
+            chip.release_key(0x5);
+            chip.execute_cycle().unwrap();
+            let replay = chip.stop_recording().unwrap();
+            assert_eq!(replay.events, vec![KeyEvent { cycle: 0, key: 0x5, pressed: true }, KeyEvent { cycle: 2, key: 0x5, pressed: false }]);
+
+            let mut replayed = Chip8::new();
+            replayed.memory[program_init..program_init + program.len()].copy_from_slice(&program);
+            replayed.start_replay(replay);
+            // live input is ignored while replaying
+            replayed.press_key(0xA);
+            replayed.execute_cycle().unwrap();
+            assert_eq!(replayed.get_register_value(0x00), random);
+            assert_eq!(replayed.get_pressed_key(), Some(0x5));
+            replayed.execute_cycle().unwrap();
+            assert!(replayed.is_key_pressed(0x5));
+            replayed.execute_cycle().unwrap();
+            assert_eq!(replayed.get_pressed_key(), None);
+            assert!(!replayed.is_replaying());
+        }
+
+        #[test]
+        fn record_mid_session_test() {
+            let program_init = 0x0200;
+            // RND V0, 0xFF then JP back to it
+            let program = [0xC0, 0xFF, 0x12, 0x00];
+            let mut chip = Chip8::new();
+            chip.config.timer_mode = TimerMode::Threaded;
+            chip.memory[program_init..program_init + program.len()].copy_from_slice(&program);
+            chip.set_delay_timer(30, None);
+            for _ in 0..3 {
+                chip.execute_cycle().unwrap();
+            }
+            // recording forces paced timers, the running one is carried over
+            chip.start_recording();
+            assert_eq!(chip.config.timer_mode, TimerMode::Cycles);
+            assert!(chip.delay_timer.as_ref().unwrap().1.is_none());
+            assert!(chip.get_delay_timer_value() > 0);
+            chip.execute_cycle().unwrap();
+            chip.execute_cycle().unwrap();
+            let random = chip.get_register_value(0x00);
+            let replay = chip.stop_recording().unwrap();
+            assert_eq!((replay.start_cycle, replay.timer_mode), (3, TimerMode::Cycles));
+
+            // same state as when the recording started
+            let mut replayed = Chip8::new();
+            replayed.config.timer_mode = TimerMode::Threaded;
+            replayed.memory[program_init..program_init + program.len()].copy_from_slice(&program);
+            replayed.update_pc(Some(0x0202));
+            replayed.start_replay(replay);
+            assert_eq!((replayed.cycles, replayed.config.timer_mode), (3, TimerMode::Cycles));
+            replayed.execute_cycle().unwrap();
+            replayed.execute_cycle().unwrap();
+            assert_eq!(replayed.get_register_value(0x00), random);
+        }
+
         #[test]
         fn call_subroutine_test() {
             let mut chip = Chip8::new();
@@ -933,48 +2237,141 @@ mod tests {
             assert_eq!(chip.memory[chip.config.stack_init_addr as usize..chip.config.stack_init_addr as usize+2], [0x02, 0x00]);
             assert_eq!(chip.sp, 1);
             // return from subroutine
-            chip.leave_subroutine();
+            chip.leave_subroutine().unwrap();
             assert_eq!(chip.pc, 0x0200);
             assert_eq!(chip.sp, 0);
         }
 
+        #[test]
+        fn stack_bounds_test() {
+            let mut chip = Chip8::new();
+            assert_eq!(chip.get_stack_depth(), chip.config.stack_depth());
+            assert_eq!(chip.leave_subroutine(), Err(ChipError::StackUnderflow { pc: 0x0200 }));
+            for i in 0..chip.get_stack_depth() as u16 {
+                chip.call_subroutine(0x0300 + i*2).unwrap();
+            }
+            assert_eq!(chip.call_subroutine(0x0400), Err(ChipError::StackOverflow { pc: 0x031E }));
+            assert_eq!(chip.get_stack()[..2], [0x0200, 0x0300]);
+            chip.leave_subroutine().unwrap();
+            assert_eq!(chip.pc, 0x031C);
+            // misaligned stacks are rejected when loading
+            chip.config.stack_canary += 1;
+            assert!(chip.config.stack_region_error().is_some());
+        }
+
+        #[test]
+        fn font_region_test() {
+            let mut chip = Chip8::new();
+            assert!(!chip.config.font_region_collides());
+            // the 80 + 160 bytes of sprites have to fit before the end of memory
+            chip.config.font_addr = (chip.config.memory_size - 0xF0) as u16;
+            assert!(!chip.config.font_region_collides());
+            chip.config.font_addr += 1;
+            assert!(chip.config.font_region_collides());
+            assert!(matches!(chip.load_program(crate::chip8::ProgramType::Binary("../tests/mock_program.ch8"), None, None), Err(ChipError::InvalidConfig(_))));
+        }
+
         mod graphix {
             use super::*;
+            use crate::framebuffer::Framebuffer;
 
             #[test]
             fn get_gfx_sprite_test() {
                 let mut chip = Chip8::new();
                 // set everything up
-                chip.gfx[0] = vec![0x13, 0x14, 0x15, 0x16, 0x00, 0x00, 0x00, 0x00];
-                chip.gfx[1] = vec![0x17, 0x18, 0x19, 0x20, 0x00, 0x00, 0x00, 0x00];
-                chip.gfx[2] = vec![0x21, 0x22, 0x23, 0x24, 0x00, 0x00, 0x00, 0x00];
+                chip.gfx[0].copy_from_slice(&[0x13, 0x14, 0x15, 0x16, 0x00, 0x00, 0x00, 0x00]);
+                chip.gfx[1].copy_from_slice(&[0x17, 0x18, 0x19, 0x20, 0x00, 0x00, 0x00, 0x00]);
+                chip.gfx[2].copy_from_slice(&[0x21, 0x22, 0x23, 0x24, 0x00, 0x00, 0x00, 0x00]);
                 // test
-                assert_eq!(chip.get_gfx_sprite((0,0), 0), 0x1314151600000000);
-                assert_eq!(chip.get_gfx_sprite((0,0), 1), 0x1718192000000000);
-                assert_eq!(chip.get_gfx_sprite((0,0), 2), 0x2122232400000000);
+                assert_eq!(chip.get_gfx_sprite((0,0), 0), 0x13);
+                assert_eq!(chip.get_gfx_sprite((0,0), 1), 0x17);
+                assert_eq!(chip.get_gfx_sprite((0,0), 2), 0x21);
+                // the sprite may start anywhere in the row, and the rows wrap around
+                assert_eq!(chip.get_gfx_sprite((4,0), 0), 0x31);
+                assert_eq!(chip.get_gfx_sprite((8,31), 3), 0x22);
+                // and so do the columns
+                chip.gfx[0][7] = 0x0F;
+                assert_eq!(chip.get_gfx_sprite((60,0), 0), 0xF1);
                 // clear display
                 chip.clear_display();
-                assert_eq!(chip.gfx, vec![vec![0_u8; 8]; 32]);
+                assert_eq!(chip.gfx, Framebuffer::new(64, 32));
+                // the right half of the hi-res display
+                chip.set_high_res(true);
+                chip.gfx[5][15] = 0xAA;
+                assert_eq!(chip.get_gfx_sprite((120,5), 0), 0xAA);
             }
 
             #[test]
             fn set_gfx_sprite_test() {
                 let mut chip = Chip8::new();
 
-                chip.gfx[0] = vec![0x13, 0x14, 0x15, 0x16, 0x00, 0x00, 0x00, 0x00];
-                chip.gfx[1] = vec![0x17, 0x18, 0x19, 0x20, 0x00, 0x00, 0x00, 0x00];
-                chip.gfx[2] = vec![0x21, 0x22, 0x23, 0x24, 0x00, 0x00, 0x00, 0x00];
+                chip.gfx[0].copy_from_slice(&[0x13, 0x14, 0x15, 0x16, 0x00, 0x00, 0x00, 0x00]);
+                chip.gfx[1].copy_from_slice(&[0x17, 0x18, 0x19, 0x20, 0x00, 0x00, 0x00, 0x00]);
+                chip.gfx[2].copy_from_slice(&[0x21, 0x22, 0x23, 0x24, 0x00, 0x00, 0x00, 0x00]);
 
                 // (old) assert_eq!(chip.get_gfx_sprite((0,4), 0), 0x0310000000000000);
 
-                chip.set_gfx_sprite((0,0), 0, 0x03);
-                assert!(chip.memory[chip.registers[15] as usize] & 0x01 == 0x01); // check collision is activated
+                assert!(chip.set_gfx_sprite((0,0), 0, 0x03)); // check collision is activated
                 chip.set_gfx_sprite((0,0), 1, 0x05);
+                assert!(!chip.set_gfx_sprite((0,0), 3, 0x07)); // nothing erased on a blank row
+                assert!(chip.set_gfx_sprite((0,0), 3, 0x07)); // erasing it again collides
                 chip.set_gfx_sprite((0,0),2, 0x07);
 
-                assert_eq!(chip.get_gfx_sprite((0,0), 0), 0x1014151600000000); // 0x13 xor 0x03 = 0x10
-                assert_eq!(chip.get_gfx_sprite((0,0), 1), 0x1218192000000000); // 0x17 xor 0x05 = 0x12
-                assert_eq!(chip.get_gfx_sprite((0,0), 2), 0x2622232400000000); // 0x21 xor 0x07 = 0x26
+                assert_eq!(chip.get_gfx_sprite((0,0), 0), 0x10); // 0x13 xor 0x03 = 0x10
+                assert_eq!(chip.get_gfx_sprite((0,0), 1), 0x12); // 0x17 xor 0x05 = 0x12
+                assert_eq!(chip.get_gfx_sprite((0,0), 2), 0x26); // 0x21 xor 0x07 = 0x26
+            }
+
+            #[test]
+            fn high_res_and_scroll_test() {
+                let mut chip = Chip8::new();
+                chip.set_high_res(true);
+                assert_eq!((128, 64), chip.get_display_size());
+                assert!(!chip.set_gfx_wide_sprite((120,63), 0, 0xFFFF));
+                // wraps around to the left side
+                assert_eq!(chip.gfx[63][15], 0xFF);
+                assert_eq!(chip.gfx[63][0], 0xFF);
+                assert_eq!(chip.gfx[63][1], 0x00);
+                chip.scroll_display_down(1);
+                assert_eq!(chip.gfx[0], vec![0_u8; 16]);
+                chip.set_gfx_sprite((0,0), 0, 0xF0);
+                chip.scroll_display_horizontal(4);
+                assert_eq!(chip.gfx[0][0], 0x0F);
+                chip.scroll_display_horizontal(-4);
+                assert_eq!(chip.gfx[0][0], 0xF0);
+                chip.set_high_res(false);
+                assert_eq!(chip.gfx, Framebuffer::new(64, 32));
+            }
+
+            #[test]
+            fn sprite_clip_test() {
+                let mut chip = Chip8::new();
+                chip.config.quirks.clip_sprites = true;
+                // starting position wraps, (66, 33) is (2, 1)
+                assert!(!chip.set_gfx_sprite((66,33), 0, 0xFF));
+                assert_eq!(chip.gfx[1][0], 0x3F);
+                assert_eq!(chip.gfx[1][1], 0xC0);
+                // rows and columns past the edges are dropped
+                chip.set_gfx_sprite((60,31), 0, 0xFF);
+                chip.set_gfx_sprite((60,31), 1, 0xFF);
+                assert_eq!(chip.gfx[31][7], 0x0F);
+                assert_eq!(chip.gfx[31][0], 0x00);
+                assert_eq!(chip.gfx[0], vec![0_u8; 8]);
+                assert_eq!(chip.get_gfx_sprite((60,31), 1), 0x00);
+            }
+
+            #[test]
+            fn planes_test() {
+                let mut chip = Chip8::new();
+                chip.set_gfx_sprite((0,0), 0, 0xFF);
+                chip.select_planes(0x02);
+                chip.set_gfx_plane_sprite(1, (0,0), 0, 0x0F, 8);
+                // only the selected plane is cleared
+                chip.clear_display();
+                assert_eq!(chip.get_gfx_plane(0)[0][0], 0xFF);
+                assert_eq!(chip.get_gfx_plane(1)[0][0], 0x00);
+                chip.select_planes(0x03);
+                assert_eq!(chip.get_selected_planes(), vec![0, 1]);
             }
         }
 
@@ -986,11 +2383,13 @@ mod tests {
             // declare routines
             chip.routines.push(RoutineParams{
                 addr: None,
-                purpose: crate::chip8::RoutinePurpose::DelayTimer
+                purpose: crate::chip8::RoutinePurpose::DelayTimer,
+                priority: None
             });
             chip.routines.push(RoutineParams{
                 addr: None,
-                purpose: crate::chip8::RoutinePurpose::SoundTimer
+                purpose: crate::chip8::RoutinePurpose::SoundTimer,
+                priority: None
             });
             // set register values
             chip.memory[(chip.config.first_register_addr as usize) + 3] = 0x02;
@@ -1,9 +1,12 @@
 
 use serde::{Serialize, Deserialize};
 use std::fs;
-use serde_yaml;
-use super::chip8::ChipConfig;
-use clap::Parser;
+use super::chip8::{ChipConfig, FlagMode};
+use super::quirks::{Quirks, QuirksPreset, LoadStoreIncrement};
+use super::timers::TimerMode;
+use super::protection::{MemoryRegion, default_regions};
+use super::audio::Waveform;
+use clap::{Parser, ValueEnum};
 
 // chip constants
 
@@ -15,16 +18,55 @@ pub const RTI_DEFAULT_ADDR: u16 = 0x600;
 const DISPLAY_HEIGHT: usize = 32;
 const DISPLAY_WIDTH: usize = 64;
 const EOP_OPT_CODE: u16 = 0x0000;
+const FONT_ADDR: u16 = 0x050; // built-in hexadecimal sprites, right after the stack canary
+const FLAG_MODE: FlagMode = FlagMode::Partitioned;
+const MEMORY_SIZE: usize = 4096; // 65536 for XO-CHIP programs
+const EXTENDED_SPRITES: bool = false;
+const TIMER_MODE: TimerMode = TimerMode::Threaded;
+const INSTRUCTIONS_PER_FRAME: u32 = 10; // 600Hz
+const PERMISSIVE_MEMORY: bool = false;
+const RNG_SEED: Option<u64> = None; // picked at random
+pub const FRAME_HZ: u32 = 60;
 
 // display constants
 
 const WINDOW_WIDTH: u32 = 768;
 const WINDOW_HEIGHT: u32 = 600;
+const SCALE: Option<u32> = None; // window_width and window_height are used unless set
+const FOREGROUND: Rgb = Rgb(255, 0, 0);
+const BACKGROUND: Rgb = Rgb(0, 255, 0);
+const PALETTE: [Rgb; 2] = [Rgb(0, 0, 255), Rgb(255, 255, 0)]; // second plane, both planes
+const GRID: bool = false;
+const FULLSCREEN: bool = false;
+const TITLE: &str = "chip8-rusterpreter";
+const VOLUME: f32 = 0.25; // from 0 to 1
+const MUTE: bool = false;
+const WAVEFORM: Waveform = Waveform::Square;
+const TONE_HZ: u32 = 440;
+
+///	Color written as `[red, green, blue]` in the config file
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DisplayConfig {
     pub window_width: u32,
-    pub window_height: u32
+    pub window_height: u32,
+    /// host pixels per chip pixel, the window is sized after the display when set
+    pub scale: Option<u32>,
+    /// color of the pixels lit in the first plane
+    pub foreground: Rgb,
+    pub background: Rgb,
+    /// colors of the other plane combinations, see `DisplayConfig::plane_color`
+    pub palette: Vec<Rgb>,
+    /// leaves a one pixel gap around each chip pixel
+    pub grid: bool,
+    pub fullscreen: bool,
+    pub title: String,
+    pub volume: f32,
+    pub mute: bool,
+    pub waveform: Waveform,
+    pub tone_hz: u32
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -33,24 +75,89 @@ pub struct Config {
     display: DisplayConfig
 }
 
+///	Entries of the config file, written as `!Chip` and `!Display` in YAML
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub enum ConfigParserEnum {
-    Chip {
-        first_register_addr: Option<u16>,
-        stack_init_addr: Option<u16>,
-        stack_canary: Option<u16>,
-        program_init: Option<u16>,
-        rti_default_addr: Option<u16>,
-        display_height: Option<usize>,
-        display_width: Option<usize>,
-        eop_opt_code: Option<u16>
-    },
-    Display {
-        window_height: Option<u32>,
-        window_width: Option<u32>
+    Chip(ChipParser),
+    Display(DisplayParser)
+}
+
+/// Chip entry as written in the config file, missing fields take the defaults above
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct ChipParser {
+    first_register_addr: Option<u16>,
+    stack_init_addr: Option<u16>,
+    stack_canary: Option<u16>,
+    program_init: Option<u16>,
+    rti_default_addr: Option<u16>,
+    display_height: Option<usize>,
+    display_width: Option<usize>,
+    eop_opt_code: Option<u16>,
+    font_addr: Option<u16>,
+    memory_size: Option<usize>,
+    extended_sprites: Option<bool>,
+    timer_mode: Option<TimerMode>,
+    instructions_per_frame: Option<u32>,
+    cpu_hz: Option<u32>,
+    flag_mode: Option<FlagMode>,
+    rng_seed: Option<u64>,
+    quirks: Option<QuirksParser>,
+    protected_regions: Option<Vec<MemoryRegion>>,
+    permissive_memory: Option<bool>
+}
+
+/// Display entry as written in the config file, missing fields take the defaults above
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct DisplayParser {
+    window_height: Option<u32>,
+    window_width: Option<u32>,
+    scale: Option<u32>,
+    foreground: Option<Rgb>,
+    background: Option<Rgb>,
+    palette: Option<Vec<Rgb>>,
+    grid: Option<bool>,
+    fullscreen: Option<bool>,
+    title: Option<String>,
+    volume: Option<f32>,
+    mute: Option<bool>,
+    waveform: Option<Waveform>,
+    tone_hz: Option<u32>
+}
+
+/// Quirks as written in the config file, a preset can be picked and then single quirks overridden
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct QuirksParser {
+    preset: Option<QuirksPreset>,
+    shift_uses_vy: Option<bool>,
+    load_store_increment: Option<LoadStoreIncrement>,
+    jump_uses_vx: Option<bool>,
+    clip_sprites: Option<bool>
+}
+
+impl QuirksParser {
+    fn resolve(&self) -> Quirks {
+        let base = match self.preset {
+            Some(preset) => Quirks::from_preset(preset),
+            None => Quirks::default()
+        };
+        Quirks {
+            shift_uses_vy: self.shift_uses_vy.unwrap_or(base.shift_uses_vy),
+            load_store_increment: self.load_store_increment.unwrap_or(base.load_store_increment),
+            jump_uses_vx: self.jump_uses_vx.unwrap_or(base.jump_uses_vx),
+            clip_sprites: self.clip_sprites.unwrap_or(base.clip_sprites)
+        }
     }
 }
 
+///	Formats the framebuffer can be dumped in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DumpFormat {
+    /// one line per row, `#` for lit pixels and `.` for the rest
+    Text,
+    /// plain PBM (P1) image
+    Pbm
+}
+
 #[derive(Parser, Debug)]
 #[command(author)]
 pub struct Args {  
@@ -58,184 +165,196 @@ pub struct Args {
     pub file: String,
 
     #[arg(long, short)]
-    config: Option<String>
+    config: Option<String>,
+
+    /// load `file` as a binary .ch8 ROM instead of assembly text
+    #[arg(long, short)]
+    pub binary: bool,
+
+    /// restore a state saved with the save slot hotkeys after loading `file`
+    #[arg(long)]
+    pub load_state: Option<String>,
+
+    /// record every key press and release to this replay file
+    #[arg(long)]
+    pub record: Option<String>,
+
+    /// play the key presses of a recorded replay file instead of live input
+    #[arg(long)]
+    pub replay: Option<String>,
+
+    /// headless runner: cycles to run, the program runs until it ends if neither this nor `frames` is set
+    #[arg(long, conflicts_with = "frames")]
+    pub cycles: Option<u64>,
+
+    /// headless runner: frames of instructions_per_frame cycles to run
+    #[arg(long)]
+    pub frames: Option<u64>,
+
+    /// headless runner: format of the final framebuffer dump
+    #[arg(long, value_enum, default_value = "text")]
+    pub dump: DumpFormat,
+
+    /// headless runner: file to write the framebuffer dump to instead of stdout
+    #[arg(long)]
+    pub dump_file: Option<String>,
+
+    /// headless runner: write the tone played while the sound timer is non-zero to this WAV file
+    #[arg(long)]
+    pub wav: Option<String>
+}
+
+///	Entries of the file passed with `--config`, none without a config file
+fn read_config_entries() -> Vec<ConfigParserEnum> {
+    match Args::parse().config {
+        Some(file) => serde_yaml::from_str(&fs::read_to_string(file).unwrap()).unwrap(),
+        None => Vec::new()
+    }
 }
 
 pub fn parse_chip() -> ChipConfig {
+    // the Chip entry may come first or second, or be missing in a Display only file
+    let chip = read_config_entries().into_iter().find_map(|conf| match conf {
+        ConfigParserEnum::Chip(chip) => Some(chip),
+        _ => None
+    }).unwrap_or_default();
+    ChipConfig {
+        first_register_addr: chip.first_register_addr.unwrap_or(FIRST_REGISTER_ADDR),
+        display_height: chip.display_height.unwrap_or(DISPLAY_HEIGHT),
+        display_width: chip.display_width.unwrap_or(DISPLAY_WIDTH),
+        program_init: chip.program_init.unwrap_or(PROGRAM_INIT_ADDR),
+        rti_default_addr: chip.rti_default_addr.unwrap_or(RTI_DEFAULT_ADDR),
+        eop_opt_code: chip.eop_opt_code.unwrap_or(EOP_OPT_CODE),
+        stack_canary: chip.stack_canary.unwrap_or(STACK_CANARY),
+        font_addr: chip.font_addr.unwrap_or(FONT_ADDR),
+        memory_size: chip.memory_size.unwrap_or(MEMORY_SIZE),
+        extended_sprites: chip.extended_sprites.unwrap_or(EXTENDED_SPRITES),
+        timer_mode: chip.timer_mode.unwrap_or(TIMER_MODE),
+        // an explicit instructions_per_frame wins over the clock
+        instructions_per_frame: chip.instructions_per_frame
+            .or(chip.cpu_hz.map(|hz| (hz / FRAME_HZ).max(1)))
+            .unwrap_or(INSTRUCTIONS_PER_FRAME),
+        flag_mode: chip.flag_mode.unwrap_or(FLAG_MODE),
+        rng_seed: chip.rng_seed.or(RNG_SEED),
+        quirks: chip.quirks.map(|quirks| quirks.resolve()).unwrap_or_default(),
+        protected_regions: chip.protected_regions.unwrap_or_else(default_regions),
+        permissive_memory: chip.permissive_memory.unwrap_or(PERMISSIVE_MEMORY),
+        stack_init_addr: chip.stack_init_addr.unwrap_or(STACK_INIT_ADDR)
+    }
+}
+
 
-    let mut chip_conf: Option<ConfigParserEnum> = None;
-    if let Some(file) = Args::parse().config {  
-        let file = fs::read_to_string(file).unwrap();
-        let deserialized_chip_config: Vec<ConfigParserEnum> = serde_yaml::from_str(&file).unwrap();
-        if deserialized_chip_config.len() > 0 {
-            if let ConfigParserEnum::Chip { .. } = deserialized_chip_config[0] {
-                chip_conf = Some(deserialized_chip_config[0].clone());
-            }
-            else if let ConfigParserEnum::Chip { .. } = deserialized_chip_config[1] {
-                chip_conf = Some(deserialized_chip_config[1].clone());
-            }
+impl DisplayConfig {
+    ///	Window size for a chip display of `display_size` pixels, `scale` wins over the window size if set
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `display_size` - _(width, height) of the chip display in low resolution_
+    pub fn window_size(&self, display_size: (usize, usize)) -> (u32, u32) {
+        match self.scale {
+            Some(scale) => (display_size.0 as u32 * scale, display_size.1 as u32 * scale),
+            None => (self.window_width, self.window_height)
         }
     }
-    ChipConfig {
-        first_register_addr: match chip_conf { 
-            Some(ref chip)  =>  { 
-                if let ConfigParserEnum::Chip{first_register_addr, ..} = chip { 
-                
-                    match first_register_addr {
-                        Some(target) => target.clone(),
-                        None => FIRST_REGISTER_ADDR
-                    }
-                } 
-                else {
-                    FIRST_REGISTER_ADDR
-                }
-            }, 
-            None => FIRST_REGISTER_ADDR 
-        },
-        display_height: match chip_conf { 
-            Some(ref chip)  =>  { 
-                if let ConfigParserEnum::Chip{display_height, ..} = chip { 
-                    match display_height {
-                        Some(target) => target.clone(),
-                        None => DISPLAY_HEIGHT
-                    }
-                } 
-                else {
-                    DISPLAY_HEIGHT
-                }
-            }, 
-            None => DISPLAY_HEIGHT 
-        },
-        display_width: match chip_conf { 
-            Some(ref chip)  =>  { 
-                if let ConfigParserEnum::Chip{display_width, ..} = chip { 
-                    match display_width {
-                        Some(target) => target.clone(),
-                        None => DISPLAY_WIDTH
-                    }
-                }
-                else {
-                    DISPLAY_WIDTH
-                } 
-            }, 
-            None => DISPLAY_WIDTH 
-        },
-        program_init: match chip_conf { 
-            Some(ref chip)  =>  { 
-                if let ConfigParserEnum::Chip{program_init, ..} = chip { 
-                    match program_init {
-                        Some(target) => target.clone(),
-                        None => PROGRAM_INIT_ADDR
-                    }
-                } 
-                else {
-                    PROGRAM_INIT_ADDR
-                }
-            }, 
-            None => PROGRAM_INIT_ADDR 
-        },
-        rti_default_addr: match chip_conf { 
-            Some(ref chip)  =>  { 
-                if let ConfigParserEnum::Chip{rti_default_addr, ..} = chip { 
-                    match rti_default_addr {
-                        Some(target) => target.clone(),
-                        None => RTI_DEFAULT_ADDR
-                    }
-                } 
-                else {
-                    RTI_DEFAULT_ADDR
-                }
-            }, 
-            None => RTI_DEFAULT_ADDR 
-        },
-        eop_opt_code: match chip_conf { 
-            Some(ref chip)  =>  { 
-                if let ConfigParserEnum::Chip{eop_opt_code, ..} = chip { 
-                    match eop_opt_code {
-                        Some(target) => target.clone(),
-                        None => EOP_OPT_CODE
-                    }
-                }
-                else {
-                    EOP_OPT_CODE
-                }
-            }, 
-            None => EOP_OPT_CODE 
-        },
-        stack_canary: match chip_conf { 
-            Some(ref chip)  =>  { 
-                if let ConfigParserEnum::Chip{stack_canary, ..} = chip { 
-                    match stack_canary {
-                        Some(target) => target.clone(),
-                        None => STACK_CANARY
-                    }
-                } 
-                else {
-                    STACK_CANARY
-                }
-            }, 
-            None => STACK_CANARY 
-        },
-        stack_init_addr: match chip_conf { 
-            Some(ref chip)  =>  { 
-                if let ConfigParserEnum::Chip{stack_init_addr, ..} = chip { 
-                    match stack_init_addr {
-                        Some(target) => target.clone(),
-                        None => STACK_INIT_ADDR
-                    }
-                }
-                else {
-                    STACK_INIT_ADDR
-                }
-            }, 
-            None => STACK_INIT_ADDR 
+
+    ///	Color of a pixel lit in the planes set in `planes`, bit 0 being the first plane
+    /// The first plane alone uses `foreground`, every other combination takes the next `palette` entry
+    /// (second plane, both planes, third plane...), combinations past the palette fall back to `foreground`
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `planes` - _bitmask of the planes lit at the pixel, 0 is the background_
+    pub fn plane_color(&self, planes: usize) -> Rgb {
+        match planes {
+            0 => self.background,
+            1 => self.foreground,
+            _ => *self.palette.get(planes - 2).unwrap_or(&self.foreground)
         }
     }
 }
 
-
 pub fn parse_display() -> DisplayConfig {
-    let file = fs::read_to_string("chip8-config.yaml").unwrap();
-    let deserialized_chip_config: Vec<ConfigParserEnum> = serde_yaml::from_str(&file).unwrap();
-    let mut chip_conf: Option<ConfigParserEnum> = None;
-    if deserialized_chip_config.len() > 0 {
-        if let ConfigParserEnum::Chip { .. } = deserialized_chip_config[0] {
-            chip_conf = Some(deserialized_chip_config[0].clone());
+    // same file as the Chip entry, the Display entry may come first or second
+    let display = read_config_entries().into_iter().find_map(|conf| match conf {
+        ConfigParserEnum::Display(display) => Some(display),
+        _ => None
+    }).unwrap_or_default();
+    DisplayConfig {
+        window_height: display.window_height.unwrap_or(WINDOW_HEIGHT),
+        window_width: display.window_width.unwrap_or(WINDOW_WIDTH),
+        scale: display.scale.or(SCALE),
+        foreground: display.foreground.unwrap_or(FOREGROUND),
+        background: display.background.unwrap_or(BACKGROUND),
+        palette: display.palette.unwrap_or_else(|| PALETTE.to_vec()),
+        grid: display.grid.unwrap_or(GRID),
+        fullscreen: display.fullscreen.unwrap_or(FULLSCREEN),
+        title: display.title.unwrap_or_else(|| TITLE.to_string()),
+        volume: display.volume.unwrap_or(VOLUME),
+        mute: display.mute.unwrap_or(MUTE),
+        waveform: display.waveform.unwrap_or(WAVEFORM),
+        tone_hz: display.tone_hz.unwrap_or(TONE_HZ)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigParserEnum, ChipParser, DisplayParser, DisplayConfig, Rgb};
+    use crate::audio::Waveform;
+    use crate::quirks::{Quirks, QuirksPreset};
+
+    #[test]
+    fn parse_quirks_from_yaml() {
+        let yaml = "- !Chip\n  quirks:\n    preset: CosmacVip\n    clip_sprites: false\n";
+        let parsed: Vec<ConfigParserEnum> = serde_yaml::from_str(yaml).unwrap();
+        if let ConfigParserEnum::Chip(ChipParser { quirks: Some(quirks), .. }) = &parsed[0] {
+            assert_eq!(quirks.resolve(), Quirks {
+                clip_sprites: false,
+                ..Quirks::from_preset(QuirksPreset::CosmacVip)
+            });
+        } else {
+            panic!("quirks not parsed: {:?}", parsed);
         }
-        else if let ConfigParserEnum::Chip { .. } = deserialized_chip_config[1] {
-            chip_conf = Some(deserialized_chip_config[1].clone());
+    }
+
+    #[test]
+    fn parse_sound_from_yaml() {
+        let yaml = "- !Chip\n  rti_default_addr: 0x700\n- !Display\n  volume: 0.5\n  waveform: Sine\n";
+        let parsed: Vec<ConfigParserEnum> = serde_yaml::from_str(yaml).unwrap();
+        if let ConfigParserEnum::Display(DisplayParser { volume, mute, waveform, .. }) = &parsed[1] {
+            assert_eq!((*volume, *mute, *waveform), (Some(0.5), None, Some(Waveform::Sine)));
+        } else {
+            panic!("display not parsed: {:?}", parsed);
         }
     }
-    DisplayConfig {
-        window_height: match chip_conf { 
-            Some(ref chip)  =>  { 
-                if let ConfigParserEnum::Display { window_height, .. } = chip { 
-                
-                    match window_height {
-                        Some(target) => target.clone(),
-                        None => WINDOW_HEIGHT
-                    }
-                } 
-                else {
-                    WINDOW_HEIGHT
-                }
-            }, 
-            None => WINDOW_HEIGHT 
-        },
-        window_width: match chip_conf { 
-            Some(ref chip)  =>  { 
-                if let ConfigParserEnum::Display { window_width, .. } = chip { 
-                
-                    match window_width {
-                        Some(target) => target.clone(),
-                        None => WINDOW_WIDTH
-                    }
-                } 
-                else {
-                    WINDOW_WIDTH
-                }
-            }, 
-            None => WINDOW_WIDTH 
-        },
+
+    #[test]
+    fn parse_theme_from_yaml() {
+        let yaml = "- !Display\n  scale: 10\n  foreground: [255, 255, 255]\n  palette: [[1, 2, 3]]\n  title: Pong\n";
+        let parsed: Vec<ConfigParserEnum> = serde_yaml::from_str(yaml).unwrap();
+        if let ConfigParserEnum::Display(DisplayParser { scale, foreground, palette: Some(palette), title, .. }) = &parsed[0] {
+            assert_eq!((*scale, *foreground, title.as_deref()), (Some(10), Some(Rgb(255, 255, 255)), Some("Pong")));
+            let config = DisplayConfig {
+                window_width: 768,
+                window_height: 600,
+                scale: *scale,
+                foreground: Rgb(255, 255, 255),
+                background: Rgb(0, 0, 0),
+                palette: palette.clone(),
+                grid: false,
+                fullscreen: false,
+                title: "Pong".to_string(),
+                volume: 0.25,
+                mute: false,
+                waveform: Waveform::Square,
+                tone_hz: 440
+            };
+            assert_eq!(config.window_size((64, 32)), (640, 320));
+            assert_eq!(config.plane_color(0), Rgb(0, 0, 0));
+            assert_eq!(config.plane_color(2), Rgb(1, 2, 3));
+            // no palette entry for both planes
+            assert_eq!(config.plane_color(3), Rgb(255, 255, 255));
+        } else {
+            panic!("display not parsed: {:?}", parsed);
+        }
     }
-}
\ No newline at end of file
+}
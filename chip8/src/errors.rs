@@ -0,0 +1,83 @@
+use std::fmt;
+
+use crate::protection::MemoryAccess;
+
+///	Reasons for the chip to stop, either while loading a program or while executing it
+/// `NormalExit` is the only variant not caused by a fault
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChipError {
+    /// the end of program opcode or 00FD - EXIT was reached
+    NormalExit { pc: u16 },
+    /// the opcode at `pc` does not decode to any instruction
+    IllegalOpcode { pc: u16, opcode: u16 },
+    /// the instruction at `pc` belongs to an extension disabled in the config
+    ExtensionDisabled { pc: u16, opcode: u16, extension: &'static str },
+    /// a call at `pc` reached the stack canary
+    StackOverflow { pc: u16 },
+    /// a return at `pc` found the stack empty
+    StackUnderflow { pc: u16 },
+    /// the instruction at `pc` jumps, calls or reaches through I outside program memory
+    AddressOutOfBounds { pc: u16, addr: u16 },
+    /// the instruction at `pc` accessed `addr` in a way its protected region forbids
+    ProtectionFault { pc: u16, addr: u16, access: MemoryAccess },
+    /// the ROM does not fit in memory when loaded at `addr`
+    RomTooLarge { size: usize, addr: u16 },
+    /// the configuration cannot hold a program
+    InvalidConfig(String),
+    /// the program file could not be read
+    Io(String),
+    /// the program text could not be assembled
+    Syntax(String),
+    /// the saved state could not be read or restored
+    Snapshot(String),
+    /// the input replay could not be read
+    Replay(String)
+}
+
+impl ChipError {
+    ///	Exit status of the program, 0 for a normal exit and 1 for any fault
+    pub fn status(&self) -> u8 {
+        match self {
+            ChipError::NormalExit { .. } => 0,
+            _ => 1
+        }
+    }
+}
+
+impl fmt::Display for ChipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChipError::NormalExit { pc } => write!(f, "Program exited at {:#06x}", pc),
+            ChipError::IllegalOpcode { pc, opcode } => write!(f, "Illegal opcode {:#06x} at {:#06x}", opcode, pc),
+            ChipError::ExtensionDisabled { pc, opcode, extension } => write!(f, "Opcode {:#06x} at {:#06x} requires {}", opcode, pc, extension),
+            ChipError::StackOverflow { pc } => write!(f, "Stack overflow at {:#06x}: reached stack canary", pc),
+            ChipError::StackUnderflow { pc } => write!(f, "Stack underflow at {:#06x}: return with an empty stack", pc),
+            ChipError::AddressOutOfBounds { pc, addr } => write!(f, "Address {:#06x} out of bounds at {:#06x}", addr, pc),
+            ChipError::ProtectionFault { pc, addr, access } => match access {
+                MemoryAccess::Write => write!(f, "Write to protected address {:#06x} at {:#06x}", addr, pc),
+                MemoryAccess::Execute => write!(f, "Execution of protected address {:#06x}", addr)
+            },
+            ChipError::RomTooLarge { size, addr } => write!(f, "ROM of {} bytes does not fit in memory from {:#06x}", size, addr),
+            ChipError::InvalidConfig(cause) => write!(f, "Invalid config: {}", cause),
+            ChipError::Io(cause) => write!(f, "I/O Error: {}", cause),
+            ChipError::Syntax(cause) => write!(f, "Syntax error: {}", cause),
+            ChipError::Snapshot(cause) => write!(f, "Invalid snapshot: {}", cause),
+            ChipError::Replay(cause) => write!(f, "Invalid replay: {}", cause)
+        }
+    }
+}
+
+impl std::error::Error for ChipError {}
+
+#[cfg(test)]
+mod tests {
+    use super::ChipError;
+
+    #[test]
+    fn status_and_display() {
+        assert_eq!(0, ChipError::NormalExit { pc: 0x240 }.status());
+        let fault = ChipError::IllegalOpcode { pc: 0x202, opcode: 0x8128 };
+        assert_eq!(1, fault.status());
+        assert_eq!("Illegal opcode 0x8128 at 0x0202", fault.to_string());
+    }
+}
@@ -0,0 +1,150 @@
+use std::ops::{Index, IndexMut};
+use serde::{Serialize, Deserialize};
+
+///	One display plane of any resolution, one bit per pixel
+/// Rows are packed in bytes with the MSB of each byte as the leftmost pixel, indexing returns those packed rows
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    rows: Vec<Vec<u8>>
+}
+
+impl Framebuffer {
+    ///	Creates a blank framebuffer
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `width` - _pixels per row_
+    /// * `height` - _rows_
+    pub fn new(width: usize, height: usize) -> Self {
+        Framebuffer {
+            width,
+            height,
+            rows: vec![Framebuffer::blank_row(width); height]
+        }
+    }
+
+    fn blank_row(width: usize) -> Vec<u8> {
+        vec![0_u8; width.div_ceil(8)]
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_pixel(&self, x: usize, y: usize) -> bool {
+        self.rows[y][x / 8] & (0x80 >> (x % 8)) != 0
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, lit: bool) {
+        if lit {
+            self.rows[y][x / 8] |= 0x80 >> (x % 8);
+        } else {
+            self.rows[y][x / 8] &= !(0x80 >> (x % 8));
+        }
+    }
+
+    ///	Pixels of row `y` from left to right
+    pub fn row_pixels(&self, y: usize) -> impl Iterator<Item = bool> + '_ {
+        (0..self.width).map(move |x| self.get_pixel(x, y))
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.iter_mut().for_each(|row| row.fill(0));
+    }
+
+    ///	XORs the `sprite_width` LSB of `sprite` into row `y` starting at column `x`, leftmost pixel first
+    /// Returns true if any pixel was erased (collision)
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `x` - _column of the leftmost pixel, wrapped around the width_
+    /// * `y` - _row, wrapped around the height_
+    /// * `sprite` - _sprite row_
+    /// * `sprite_width` - _pixels in the sprite row (up to 16)_
+    /// * `clip` - _drop the pixels past the right edge instead of wrapping them to the left_
+    pub fn xor_blit(&mut self, x: usize, y: usize, sprite: u16, sprite_width: usize, clip: bool) -> bool {
+        let (x, y) = (x % self.width, y % self.height);
+        let mut collision = false;
+        for bit in 0..sprite_width {
+            if clip && x + bit >= self.width {
+                break;
+            }
+            if (sprite >> (sprite_width - 1 - bit)) & 0x01 == 0x01 {
+                let column = (x + bit) % self.width;
+                let lit = self.get_pixel(column, y);
+                collision |= lit;
+                self.set_pixel(column, y, !lit);
+            }
+        }
+        collision
+    }
+
+    ///	Scrolls down `rows` pixels, rows entering from the top are blank
+    pub fn scroll_down(&mut self, rows: usize) {
+        let rows = rows.min(self.height);
+        self.rows.truncate(self.height - rows);
+        for _ in 0..rows {
+            self.rows.insert(0, Framebuffer::blank_row(self.width));
+        }
+    }
+
+    ///	Scrolls `columns` pixels, right if positive and left if negative, columns entering from the edge are blank
+    pub fn scroll_horizontal(&mut self, columns: i32) {
+        for y in 0..self.height {
+            let row: Vec<bool> = self.row_pixels(y).collect();
+            for x in 0..self.width {
+                let source = x as i32 - columns;
+                self.set_pixel(x, y, source >= 0 && (source as usize) < self.width && row[source as usize]);
+            }
+        }
+    }
+}
+
+impl Index<usize> for Framebuffer {
+    type Output = [u8];
+
+    fn index(&self, y: usize) -> &[u8] {
+        &self.rows[y]
+    }
+}
+
+impl IndexMut<usize> for Framebuffer {
+    fn index_mut(&mut self, y: usize) -> &mut [u8] {
+        &mut self.rows[y]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Framebuffer;
+
+    #[test]
+    fn pixels_and_blit() {
+        // not a multiple of 8 wide
+        let mut fb = Framebuffer::new(12, 5);
+        assert_eq!(fb[0].len(), 2);
+        fb.set_pixel(11, 4, true);
+        assert!(fb.get_pixel(11, 4));
+        assert_eq!(fb[4], [0x00, 0x10]);
+        // wraps to the left edge and collides with the lit pixel
+        assert!(fb.xor_blit(10, 9, 0b1110, 4, false));
+        assert_eq!(fb.row_pixels(4).collect::<Vec<_>>(), [true, false, false, false, false, false, false, false, false, false, true, false]);
+        fb.clear();
+        assert!(!fb.xor_blit(10, 0, 0b1110, 4, true));
+        assert_eq!(fb[0], [0x00, 0x30]);
+        fb.scroll_down(1);
+        fb.scroll_horizontal(1);
+        assert!(fb.get_pixel(11, 1));
+        assert_eq!(fb, {
+            let mut expected = Framebuffer::new(12, 5);
+            expected.set_pixel(11, 1, true);
+            expected
+        });
+    }
+}
@@ -0,0 +1,145 @@
+use crate::audio::WavRecorder;
+use crate::chip8::Chip8;
+use crate::config::DumpFormat;
+use crate::errors::ChipError;
+
+///	When the headless runner stops, besides the end of the program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunLimit {
+    Cycles(u64),
+    /// frames of `instructions_per_frame` cycles, run back to back without pacing
+    Frames(u64),
+    UntilExit
+}
+
+///	Runs `chip` without any display until `limit` is reached
+/// Returns the error stopping it earlier, `ChipError::NormalExit` if the program ended
+///
+///	# _Arguments_
+///
+/// * `chip` - _chip with its program already loaded_
+/// * `limit` - _cycles or frames to run, or none to run until the program ends_
+pub fn run(chip: &mut Chip8, limit: RunLimit) -> Result<(), ChipError> {
+    run_frames(chip, limit, None)
+}
+
+///	Same as `run`, also rendering the sound into `recorder` after every frame
+/// The tone plays during the frames ending with a non-zero sound timer
+///
+///	# _Arguments_
+///
+/// * `chip` - _chip with its program already loaded_
+/// * `limit` - _cycles or frames to run, or none to run until the program ends_
+/// * `recorder` - _WAV backend receiving one frame of audio every instructions_per_frame cycles_
+pub fn run_with_audio(chip: &mut Chip8, limit: RunLimit, recorder: &mut WavRecorder) -> Result<(), ChipError> {
+    run_frames(chip, limit, Some(recorder))
+}
+
+fn run_frames(chip: &mut Chip8, limit: RunLimit, mut recorder: Option<&mut WavRecorder>) -> Result<(), ChipError> {
+    let frame_cycles = (chip.get_config().instructions_per_frame as u64).max(1);
+    let cycles = match limit {
+        RunLimit::Cycles(n) => Some(n),
+        RunLimit::Frames(n) => Some(n * frame_cycles),
+        RunLimit::UntilExit => None
+    };
+    let mut executed = 0;
+    while cycles.is_none_or(|n| executed < n) {
+        chip.execute_cycle()?;
+        executed += 1;
+        if executed.is_multiple_of(frame_cycles) {
+            if let Some(recorder) = recorder.as_mut() {
+                recorder.push_frame(chip.get_sound_timer_value() > 0);
+            }
+        }
+    }
+    Ok(())
+}
+
+///	Renders the first plane of the display in `format`
+///
+///	# _Arguments_
+///
+/// * `chip` - _chip whose display is dumped_
+/// * `format` - _output format_
+pub fn dump_framebuffer(chip: &Chip8, format: DumpFormat) -> String {
+    let gfx = chip.get_gfx();
+    let rows = (0..gfx.height()).map(|y| gfx.row_pixels(y));
+    match format {
+        DumpFormat::Text => rows
+            .map(|row| row.map(|lit| if lit { '#' } else { '.' }).collect::<String>() + "\n")
+            .collect(),
+        DumpFormat::Pbm => {
+            let mut pbm = format!("P1\n{} {}\n", gfx.width(), gfx.height());
+            for row in rows {
+                pbm += &row.map(|lit| if lit { "1" } else { "0" }).collect::<Vec<_>>().join(" ");
+                pbm += "\n";
+            }
+            pbm
+        }
+    }
+}
+
+///	Lists V0-VF, I, PC, SP and the timers in hexadecimal, one line
+pub fn dump_registers(chip: &Chip8) -> String {
+    let registers: Vec<String> = (0..16_u8).map(|r| format!("V{:X}={:#04x}", r, chip.get_register_value(r))).collect();
+    format!("{} I={:#06x} PC={:#06x} SP={} DT={} ST={}",
+        registers.join(" "),
+        chip.get_i_register_value(),
+        chip.get_pc(),
+        chip.get_sp(),
+        chip.get_delay_timer_value(),
+        chip.get_sound_timer_value())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, run_with_audio, dump_framebuffer, dump_registers, RunLimit};
+    use crate::audio::{ToneGenerator, WavRecorder, Waveform, SAMPLE_RATE};
+    use crate::config::FRAME_HZ;
+    use crate::config::DumpFormat;
+    use crate::chip8::Chip8;
+    use crate::errors::ChipError;
+
+    #[test]
+    fn run_and_dump() {
+        let mut chip = Chip8::new();
+        // LD V1, 0x80, LD I, 0x0300 then JP to itself, sprite of one pixel at 0x0300
+        chip.set_i_register_value(0x0200);
+        [0x61, 0x80, 0xA3, 0x00, 0x12, 0x04].iter().enumerate().for_each(|(i, b)| chip.set_memory_value(i, *b));
+        chip.set_i_register_value(0x0300);
+        chip.set_memory_value(0, 0x80);
+        run(&mut chip, RunLimit::Cycles(3)).unwrap();
+        assert_eq!(chip.get_pc(), 0x0204);
+        assert!(dump_registers(&chip).starts_with("V0=0x00 V1=0x80 V2=0x00"));
+        assert!(dump_registers(&chip).ends_with("I=0x0300 PC=0x0204 SP=0 DT=0 ST=0"));
+        // DRW V0, V0, 1 then an illegal opcode
+        chip.set_i_register_value(0x0206);
+        [0xD0, 0x01, 0x81, 0x28].iter().enumerate().for_each(|(i, b)| chip.set_memory_value(i, *b));
+        chip.set_i_register_value(0x0300);
+        chip.update_pc(Some(0x0206));
+        assert_eq!(run(&mut chip, RunLimit::UntilExit), Err(ChipError::IllegalOpcode { pc: 0x0208, opcode: 0x8128 }));
+        let text = dump_framebuffer(&chip, DumpFormat::Text);
+        assert!(text.starts_with("#....."));
+        assert_eq!(text.lines().count(), chip.get_display_size().1);
+        let pbm = dump_framebuffer(&chip, DumpFormat::Pbm);
+        assert!(pbm.starts_with("P1\n64 32\n1 0 0"));
+    }
+
+    #[test]
+    fn run_with_sound() {
+        let mut chip = Chip8::new();
+        // JP to itself, silent until the sound timer is set
+        chip.set_i_register_value(0x0200);
+        [0x12, 0x00].iter().enumerate().for_each(|(i, b)| chip.set_memory_value(i, *b));
+        let mut recorder = WavRecorder::new(ToneGenerator::new(Waveform::Square, 440, 1.0, SAMPLE_RATE));
+        run_with_audio(&mut chip, RunLimit::Frames(1), &mut recorder).unwrap();
+        let frame = (SAMPLE_RATE / FRAME_HZ) as usize;
+        assert_eq!(recorder.samples().len(), frame);
+        assert!(recorder.samples().iter().all(|sample| *sample == 0));
+        // the tone lasts a second
+        chip.set_sound_timer(0x3C, None);
+        run_with_audio(&mut chip, RunLimit::Frames(2), &mut recorder).unwrap();
+        assert_eq!(recorder.samples().len(), 3 * frame);
+        assert!(recorder.samples()[frame..].iter().any(|sample| *sample != 0));
+    }
+}
@@ -0,0 +1,124 @@
+use serde::{Serialize, Deserialize};
+
+/// Mask bit of the delay timer interrupt
+pub const DELAY_TIMER_IRQ: u8 = 0x01;
+/// Mask bit of the sound timer interrupt
+pub const SOUND_TIMER_IRQ: u8 = 0x02;
+
+///	A timer interrupt waiting to be dispatched
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Interrupt {
+    /// mask bit of the source, either `DELAY_TIMER_IRQ` or `SOUND_TIMER_IRQ`
+    pub source: u8,
+    /// address of the routine handling it
+    pub rti: u16,
+    /// higher priorities are dispatched first and can interrupt lower priority routines
+    pub priority: u8
+}
+
+///	Queues raised interrupts until they can be dispatched
+/// An interrupt is held while its source is masked or while a routine of the same or higher priority is running
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct InterruptController {
+    /// raised and not yet dispatched, in arrival order
+    pending: Vec<Interrupt>,
+    /// bitmask of the sources currently masked
+    mask: u8,
+    /// routines being run as (priority, stack pointer before the call), innermost last
+    in_service: Vec<(u8, u8)>
+}
+
+impl InterruptController {
+    pub fn new() -> Self {
+        InterruptController::default()
+    }
+
+    pub fn raise(&mut self, interrupt: Interrupt) {
+        self.pending.push(interrupt);
+    }
+
+    ///	Masks the sources in `sources`, their interrupts stay pending until unmasked
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `sources` - _bitmask of sources_
+    pub fn mask(&mut self, sources: u8) {
+        self.mask |= sources;
+    }
+
+    ///	Unmasks the sources in `sources`
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `sources` - _bitmask of sources_
+    pub fn unmask(&mut self, sources: u8) {
+        self.mask &= !sources;
+    }
+
+    #[cfg(test)]
+    pub fn get_pending(&self) -> &[Interrupt] {
+        self.pending.as_slice()
+    }
+
+    ///	Removes and returns the interrupt to dispatch now, if any
+    /// It is the oldest unmasked interrupt of the highest priority, and it must outrank the running routine
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `sp` - _stack pointer before calling the routine, used to detect its return_
+    pub fn dispatch(&mut self, sp: u8) -> Option<Interrupt> {
+        let running = self.in_service.last().map(|(priority, _)| *priority as i16).unwrap_or(-1);
+        let (index, interrupt) = self.pending.iter()
+            .enumerate()
+            .filter(|(_, irq)| self.mask & irq.source == 0 && irq.priority as i16 > running)
+            // max_by_key keeps the last maximum, reversing keeps the oldest one
+            .rev()
+            .max_by_key(|(_, irq)| irq.priority)?;
+        let interrupt = *interrupt;
+        self.pending.remove(index);
+        self.in_service.push((interrupt.priority, sp));
+        Some(interrupt)
+    }
+
+    ///	Marks the innermost routine as finished if the stack is back to where it was before calling it
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `sp` - _stack pointer after returning_
+    pub fn returned(&mut self, sp: u8) {
+        if let Some((_, call_sp)) = self.in_service.last() {
+            if *call_sp == sp {
+                self.in_service.pop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InterruptController, Interrupt, DELAY_TIMER_IRQ, SOUND_TIMER_IRQ};
+
+    #[test]
+    fn priorities_and_masking() {
+        let delay = Interrupt { source: DELAY_TIMER_IRQ, rti: 0x600, priority: 0 };
+        let sound = Interrupt { source: SOUND_TIMER_IRQ, rti: 0x700, priority: 1 };
+        let mut controller = InterruptController::new();
+        controller.raise(delay);
+        controller.raise(sound);
+        controller.mask(SOUND_TIMER_IRQ);
+        assert_eq!(controller.dispatch(0), Some(delay));
+        // masked interrupts are not lost
+        assert_eq!(controller.dispatch(1), None);
+        controller.unmask(SOUND_TIMER_IRQ);
+        // higher priority nests inside the delay routine
+        assert_eq!(controller.dispatch(1), Some(sound));
+        controller.raise(delay);
+        assert_eq!(controller.dispatch(2), None);
+        controller.returned(1);
+        // still inside the first delay routine
+        assert_eq!(controller.dispatch(1), None);
+        controller.returned(0);
+        assert_eq!(controller.dispatch(0), Some(delay));
+        assert!(controller.get_pending().is_empty());
+    }
+}
@@ -0,0 +1,87 @@
+
+/// Number of keys in the hexadecimal keypad (0x0 - 0xF)
+pub const KEYPAD_SIZE: usize = 16;
+
+///	Holds the pressed/released state of the 16-key hexadecimal keypad
+#[derive(Debug, Clone, Default)]
+pub struct Keypad {
+    keys: [bool; KEYPAD_SIZE],
+    /// bitmask of the keys pressed since `start_wait`
+    pressed_while_waiting: u16,
+    /// last key pressed and released since `start_wait`
+    released: Option<u8>
+}
+
+impl Keypad {
+    pub fn new() -> Self {
+        Keypad { keys: [false; KEYPAD_SIZE], pressed_while_waiting: 0, released: None }
+    }
+
+    ///	Marks `key` as pressed
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `key` - _key to press, only the 4 LSB are used_
+    pub fn press(&mut self, key: u8) {
+        self.keys[(key & 0x0F) as usize] = true;
+        self.pressed_while_waiting |= 1 << (key & 0x0F);
+    }
+
+    ///	Marks `key` as released
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `key` - _key to release, only the 4 LSB are used_
+    pub fn release(&mut self, key: u8) {
+        self.keys[(key & 0x0F) as usize] = false;
+        if self.pressed_while_waiting & 1 << (key & 0x0F) != 0 {
+            self.released = Some(key & 0x0F);
+        }
+    }
+
+    ///	Starts waiting for a key to be pressed and released, keys held or released before don't count
+    pub fn start_wait(&mut self) {
+        self.pressed_while_waiting = 0;
+        self.released = None;
+    }
+
+    ///	Returns the last key pressed and released since `start_wait`, None if there is none yet
+    pub fn take_released(&mut self) -> Option<u8> {
+        self.released.take()
+    }
+
+    pub fn is_pressed(&self, key: u8) -> bool {
+        self.keys[(key & 0x0F) as usize]
+    }
+
+    ///	Returns the lowest key currently pressed, None if no key is down
+    pub fn first_pressed(&self) -> Option<u8> {
+        self.keys.iter().position(|pressed| *pressed).map(|key| key as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Keypad;
+
+    #[test]
+    fn press_and_release() {
+        let mut keypad = Keypad::new();
+        assert_eq!(keypad.first_pressed(), None);
+        keypad.press(0x0A);
+        keypad.press(0x03);
+        assert!(keypad.is_pressed(0x0A));
+        assert_eq!(keypad.first_pressed(), Some(0x03));
+        keypad.release(0x03);
+        assert!(!keypad.is_pressed(0x03));
+        assert_eq!(keypad.first_pressed(), Some(0x0A));
+        // 0x0A was held before waiting
+        keypad.start_wait();
+        keypad.release(0x0A);
+        assert_eq!(keypad.take_released(), None);
+        keypad.press(0x07);
+        assert_eq!(keypad.take_released(), None);
+        keypad.release(0x07);
+        assert_eq!(keypad.take_released(), Some(0x07));
+    }
+}
@@ -3,6 +3,16 @@ pub mod operations_set;
 pub mod timers;
 pub mod chip8;
 pub mod config;
+pub mod keypad;
+pub mod quirks;
+pub mod interrupts;
+pub mod errors;
+pub mod protection;
+pub mod snapshot;
+pub mod replay;
+pub mod headless;
+pub mod framebuffer;
+pub mod audio;
 
 use chip8::{Chip8};
 
@@ -2,31 +2,76 @@ mod chip8;
 mod timers;
 mod operations_set;
 mod config;
+mod keypad;
+mod quirks;
+mod interrupts;
+mod errors;
+mod protection;
+mod snapshot;
+mod replay;
+mod framebuffer;
+mod audio;
 extern crate sdl2;
 extern crate rand;
 
 use ::chip8::config::Args;
-use config::parse_display;
+use config::{parse_display, Rgb, FRAME_HZ};
+use sdl2::audio::{AudioCallback, AudioSpecDesired};
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
 use sdl2::pixels::{Color, PixelFormatEnum};
 use sdl2::rect::Rect;
 
+use crate::audio::{ToneGenerator, SAMPLE_RATE};
 use crate::chip8::{Chip8, ProgramType};
+use crate::errors::ChipError;
+use crate::snapshot::Snapshot;
+use crate::replay::Replay;
 use clap::Parser;
+use std::thread;
+use std::time::{Duration, Instant};
 
 
+/// Feeds the SDL audio device, which is paused whenever the sound timer is 0
+struct Beeper(ToneGenerator);
+
+impl AudioCallback for Beeper {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        self.0.fill(out);
+    }
+}
 
 fn main() -> Result<(), String> {
     let mut chip = Chip8::new();
-    let file = Args::parse().file;
+    let args = Args::parse();
     let sdl2_context = sdl2::init()?;
     let video_subsystem = sdl2_context.video()?;
     let display_config = parse_display();
+    // no device at all when muted, hosts without audio run silently
+    let beeper = if display_config.mute {
+        None
+    } else {
+        let spec = AudioSpecDesired { freq: Some(SAMPLE_RATE as i32), channels: Some(1), samples: None };
+        let device = sdl2_context.audio()
+            .and_then(|audio| audio.open_playback(None, &spec, |obtained| Beeper(ToneGenerator::from_config(&display_config, obtained.freq as u32))));
+        match device {
+            Ok(device) => Some(device),
+            Err(cause) => {
+                eprintln!("No sound, the audio device could not be opened: {}", cause);
+                None
+            }
+        }
+    };
 
-    let window = video_subsystem
-        .window("rust-sdl2 demo", display_config.window_width, display_config.window_height)
-        .position_centered()
+    let (window_width, window_height) = display_config.window_size((chip.get_config().display_width, chip.get_config().display_height));
+    let mut window_builder = video_subsystem.window(&display_config.title, window_width, window_height);
+    window_builder.position_centered();
+    if display_config.fullscreen {
+        window_builder.fullscreen_desktop();
+    }
+    let window = window_builder
         .build()
         .map_err(|e| e.to_string())?;
     let mut canvas = window
@@ -36,75 +81,191 @@ fn main() -> Result<(), String> {
         .map_err(|e| e.to_string())?;
     let creator = canvas.texture_creator();
     let mut texture = creator
-        .create_texture_target(PixelFormatEnum::RGBA8888, display_config.window_width, display_config.window_height)
+        .create_texture_target(PixelFormatEnum::RGBA8888, window_width, window_height)
         .map_err(|e| e.to_string())?;
     
 
-    chip.load_program(ProgramType::Main(file.as_str()), None, None).expect("Error loading program: ");
-    loop {
-        if let Err(eop) = chip.execute_cycle() {
-            println!("Program terminated with status: {:?}", eop);
-            break;
+    let program = if args.binary { ProgramType::Binary(args.file.as_str()) } else { ProgramType::Main(args.file.as_str()) };
+    chip.load_program(program, None, None).map_err(|e| e.to_string())?;
+    if let Some(file) = &args.load_state {
+        chip.restore(Snapshot::load(file).map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+    }
+    if let Some(file) = &args.replay {
+        chip.start_replay(Replay::load(file).map_err(|e| e.to_string())?);
+    }
+    if args.record.is_some() {
+        chip.start_recording();
+    }
+    let mut event_pump = sdl2_context.event_pump()?;
+    let frame_duration = Duration::from_secs(1) / FRAME_HZ;
+    let mut next_frame = Instant::now() + frame_duration;
+    'mainloop: loop {
+        // feed the keypad once per frame, before executing its instructions
+        for event in event_pump.poll_iter() {
+            match event {
+                Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                }
+                | Event::Quit { .. } => break 'mainloop,
+                Event::KeyDown { keycode: Some(keycode), .. } => {
+                    if let Some(key) = map_keycode(keycode) {
+                        chip.press_key(key);
+                    }
+                    else if let Some((slot, save)) = map_save_slot(keycode) {
+                        let file = format!("{}.state{}", args.file, slot);
+                        let result = if save {
+                            chip.snapshot().save(&file)
+                        } else {
+                            Snapshot::load(&file).and_then(|snapshot| chip.restore(snapshot))
+                        };
+                        match result {
+                            Ok(()) => println!("{} slot {}", if save { "Saved" } else { "Loaded" }, slot),
+                            Err(cause) => println!("Slot {} unavailable: {}", slot, cause)
+                        }
+                    }
+                },
+                Event::KeyUp { keycode: Some(keycode), .. } => {
+                    if let Some(key) = map_keycode(keycode) {
+                        chip.release_key(key);
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        // run one 60Hz frame worth of instructions
+        for _ in 0..chip.get_config().instructions_per_frame {
+            if let Err(eop) = chip.execute_cycle() {
+                match eop {
+                    ChipError::NormalExit { .. } => println!("Program terminated with status: 0"),
+                    fault => println!("Program terminated with status: {}, {}", fault.status(), fault)
+                }
+                break 'mainloop;
+            }
+        }
+
+        // beep while the sound timer is non-zero
+        if let Some(device) = &beeper {
+            if chip.get_sound_timer_value() > 0 {
+                device.resume();
+            } else {
+                device.pause();
+            }
         }
         
-        let vf = chip.get_register_value(15);
-        if vf & 0x80 == 0x80 {
+        if chip.take_draw_flag() {
             // update screen
-            'mainloop: loop {
-                for event in sdl2_context.event_pump()?.poll_iter() {
-                    match event {
-                        Event::KeyDown {
-                            keycode: Some(Keycode::Escape),
-                            ..
-                        }
-                        | Event::Quit { .. } => break 'mainloop,
-                        _ => {}
+            // the resolution may change at runtime (SUPER-CHIP high resolution)
+            let (first, second) = (chip.get_gfx_plane(0), chip.get_gfx_plane(1));
+            let (width, height) = (first.width() as u32, first.height() as u32);
+            let (px_width, px_height) = (window_width / width, window_height / height);
+            // the grid is the background showing through a one pixel gap, too small pixels are left whole
+            let gap = (display_config.grid && px_width > 2 && px_height > 2) as u32;
+            // one set of rects per XO-CHIP plane combination: first plane, second plane, both
+            let mut rects = vec![Vec::new(); 3];
+            for y in 0..first.height() {
+                for (x, (lit, second_lit)) in first.row_pixels(y).zip(second.row_pixels(y)).enumerate() {
+                    let color = lit as usize | (second_lit as usize) << 1;
+                    if color != 0 {
+                        rects[color - 1].push(Rect::new((x as u32 * window_width / width) as i32,
+                        (y as u32 * window_height / height) as i32,
+                        px_width - gap,
+                        px_height - gap));
                     }
                 }
-                let mut rects = Vec::new();
-                for (no, row) in chip.get_gfx().iter().enumerate() {
-                    for (px_no, px8) in row.iter().enumerate() {
-                        
-                        for ind_px in 0..8 {
-                            
-                            
-                            if (*px8 >> ind_px) & 0x01 == 0x01 {
-                                rects.push(Rect::new((((px_no*8 + ind_px) as u32)*display_config.window_width / 64) as i32, 
-                                ((no as u32)*display_config.window_height / 32) as i32,
-                                display_config.window_width / 64,
-                                display_config.window_height / 32));
-                            }
+            }
+            let colors: Vec<Color> = (1..=rects.len()).map(|planes| to_color(display_config.plane_color(planes))).collect();
+            let background = to_color(display_config.background);
+            canvas
+                .with_texture_canvas(&mut texture, |texture_canvas| {
+                    texture_canvas.set_draw_color(background);
+                    texture_canvas.clear();
+                    for (plane_rects, color) in rects.iter().zip(colors.iter()) {
+                        if plane_rects.is_empty() {
+                            continue;
                         }
-                    }
-                }
-                canvas
-                    .with_texture_canvas(&mut texture, |texture_canvas| {
-                        //texture_canvas.clear();
-                        texture_canvas.set_draw_color(Color::RGBA(255, 0, 0, 255));
+                        texture_canvas.set_draw_color(*color);
                         texture_canvas
-                            .fill_rects(&rects)
+                            .fill_rects(plane_rects)
                             .expect("could not fill rect");
-                    })
-                    .map_err(|e| e.to_string())?;
-                canvas.set_draw_color(Color::RGBA(0, 255, 0, 0));
-                canvas.clear();
-                canvas.copy_ex(
-                    &texture,
-                    None,
-                    None,
-                    0.0,
-                    None,
-                    false,
-                    false,
-                )?;
-                canvas.present();
-            }
-
+                    }
+                })
+                .map_err(|e| e.to_string())?;
+            canvas.set_draw_color(background);
+            canvas.clear();
+            canvas.copy_ex(
+                &texture,
+                None,
+                None,
+                0.0,
+                None,
+                false,
+                false,
+            )?;
+            canvas.present();
+        }
 
-            // put the draw flag down
-            chip.set_register_value(15, vf & 0x7F);
+        // sleep what is left of the frame, late frames are not made up for
+        let now = Instant::now();
+        if now < next_frame {
+            thread::sleep(next_frame - now);
+            next_frame += frame_duration;
+        } else {
+            next_frame = now + frame_duration;
         }
     }
 
+    if let (Some(file), Some(replay)) = (&args.record, chip.stop_recording()) {
+        replay.save(file).map_err(|e| e.to_string())?;
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+fn to_color(rgb: Rgb) -> Color {
+    Color::RGBA(rgb.0, rgb.1, rgb.2, 255)
+}
+
+/// Maps the host keyboard to the hexadecimal keypad using the usual layout
+///
+/// 1 2 3 4        1 2 3 C
+/// Q W E R   ->   4 5 6 D
+/// A S D F        7 8 9 E
+/// Z X C V        A 0 B F
+fn map_keycode(keycode: Keycode) -> Option<u8> {
+    match keycode {
+        Keycode::Num1 => Some(0x1),
+        Keycode::Num2 => Some(0x2),
+        Keycode::Num3 => Some(0x3),
+        Keycode::Num4 => Some(0xC),
+        Keycode::Q => Some(0x4),
+        Keycode::W => Some(0x5),
+        Keycode::E => Some(0x6),
+        Keycode::R => Some(0xD),
+        Keycode::A => Some(0x7),
+        Keycode::S => Some(0x8),
+        Keycode::D => Some(0x9),
+        Keycode::F => Some(0xE),
+        Keycode::Z => Some(0xA),
+        Keycode::X => Some(0x0),
+        Keycode::C => Some(0xB),
+        Keycode::V => Some(0xF),
+        _ => None
+    }
+}
+
+/// Save state hotkeys, F1-F4 save to slots 1-4 and F5-F8 load them back
+/// Slots are stored next to the program as `<file>.state<slot>`
+fn map_save_slot(keycode: Keycode) -> Option<(u8, bool)> {
+    match keycode {
+        Keycode::F1 => Some((1, true)),
+        Keycode::F2 => Some((2, true)),
+        Keycode::F3 => Some((3, true)),
+        Keycode::F4 => Some((4, true)),
+        Keycode::F5 => Some((1, false)),
+        Keycode::F6 => Some((2, false)),
+        Keycode::F7 => Some((3, false)),
+        Keycode::F8 => Some((4, false)),
+        _ => None
+    }
+}
@@ -11,5 +11,7 @@ mod opt_a;
 mod opt_b;
 mod opt_c;
 mod opt_d;
+mod opt_e;
 mod opt_f;
 pub mod operations_table;
+pub mod instruction;
@@ -0,0 +1,508 @@
+use std::fmt;
+
+use crate::Chip8;
+use crate::errors::ChipError;
+use crate::interrupts::{DELAY_TIMER_IRQ, SOUND_TIMER_IRQ};
+use super::operations_table::*;
+use super::{opt_1::Opt1, opt_2::Opt2, opt_3::Opt3,
+    opt_4::Opt4, opt_5::Opt5, opt_6::Opt6,
+    opt_7::Opt7, opt_8::Opt8, opt_9::Opt9,
+    opt_a::OptA, opt_b::OptB, opt_c::OptC,
+    opt_d::{OptD, DrawHorizontal, DrawRotated}, opt_e::OptE, opt_f::OptF};
+
+///	Every instruction the interpreter understands, each one carries the operands decoded from its opcode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    /// 00E0 - CLS
+    Cls,
+    /// 00EE - RET
+    Ret,
+    /// 00Cn - SCD nibble (SUPER-CHIP)
+    ScrollDown(OperationSpecs),
+    /// 00FB - SCR (SUPER-CHIP)
+    ScrollRight,
+    /// 00FC - SCL (SUPER-CHIP)
+    ScrollLeft,
+    /// 00FD - EXIT (SUPER-CHIP)
+    Exit,
+    /// 00FE - LOW (SUPER-CHIP)
+    LowRes,
+    /// 00FF - HIGH (SUPER-CHIP)
+    HighRes,
+    /// 00Am - MASK {DT | ST}
+    MaskInterrupts(OperationSpecs),
+    /// 00Bm - UNMASK {DT | ST}
+    UnmaskInterrupts(OperationSpecs),
+    /// 009m - STOP {DT | ST}
+    StopTimers(OperationSpecs),
+    /// 008m - RESUME {DT | ST}
+    ResumeTimers(OperationSpecs),
+    /// 0nnn - SYS addr, ignored
+    Sys(OperationSpecs),
+    /// 1nnn - JP addr
+    Jump(OperationSpecs),
+    /// 2nnn - CALL addr
+    Call(OperationSpecs),
+    /// 3xkk - SE Vx, byte
+    SkipEqualByte(OperationSpecs),
+    /// 4xkk - SNE Vx, byte
+    SkipNotEqualByte(OperationSpecs),
+    /// 5xy0 - SE Vx, Vy
+    SkipEqual(OperationSpecs),
+    /// 5xy2 - SAVE Vx, Vy (XO-CHIP)
+    SaveRange(OperationSpecs),
+    /// 5xy3 - LOAD Vx, Vy (XO-CHIP)
+    LoadRange(OperationSpecs),
+    /// 6xkk - LD Vx, byte
+    LoadByte(OperationSpecs),
+    /// 7xkk - ADD Vx, byte
+    AddByte(OperationSpecs),
+    /// 8xy0 - LD Vx, Vy
+    Load(OperationSpecs),
+    /// 8xy1 - OR Vx, Vy
+    Or(OperationSpecs),
+    /// 8xy2 - AND Vx, Vy
+    And(OperationSpecs),
+    /// 8xy3 - XOR Vx, Vy
+    Xor(OperationSpecs),
+    /// 8xy4 - ADD Vx, Vy
+    Add(OperationSpecs),
+    /// 8xy5 - SUB Vx, Vy
+    Sub(OperationSpecs),
+    /// 8xy6 - SHR Vx {, Vy}
+    ShiftRight(OperationSpecs),
+    /// 8xy7 - SUBN Vx, Vy
+    SubNegated(OperationSpecs),
+    /// 8xyE - SHL Vx {, Vy}
+    ShiftLeft(OperationSpecs),
+    /// 9xy0 - SNE Vx, Vy
+    SkipNotEqual(OperationSpecs),
+    /// 9xyn - DRWH Vx, Vy, nibble (extended sprites)
+    DrawHorizontal(OperationSpecs),
+    /// Annn - LD I, addr
+    LoadI(OperationSpecs),
+    /// Bnnn - JP V0, addr
+    JumpOffset(OperationSpecs),
+    /// Cxkk - RND Vx, byte
+    Random(OperationSpecs),
+    /// Dxyn - DRW Vx, Vy, nibble
+    Draw(OperationSpecs),
+    /// Exyn - DRVH Vx, Vy, Vn (extended sprites)
+    DrawRotated(OperationSpecs),
+    /// Ex9E - SKP Vx
+    SkipKeyPressed(OperationSpecs),
+    /// ExA1 - SKNP Vx
+    SkipKeyNotPressed(OperationSpecs),
+    /// F000 NNNN - LD I, NNNN (XO-CHIP)
+    LoadLongI,
+    /// Fn01 - PLANE n (XO-CHIP)
+    Plane(OperationSpecs),
+    /// F002 - AUDIO (XO-CHIP)
+    Audio,
+    /// Fx07 - LD Vx, DT
+    LoadDelayTimer(OperationSpecs),
+    /// Fx0A - LD Vx, K
+    WaitKey(OperationSpecs),
+    /// Fx15 - LD DT, Vx
+    SetDelayTimer(OperationSpecs),
+    /// Fx18 - LD ST, Vx
+    SetSoundTimer(OperationSpecs),
+    /// Fx1E - ADD I, Vx
+    AddI(OperationSpecs),
+    /// Fx29 - LD F, Vx
+    LoadFont(OperationSpecs),
+    /// Fx30 - LD HF, Vx (SUPER-CHIP)
+    LoadBigFont(OperationSpecs),
+    /// Fx33 - LD B, Vx
+    StoreBcd(OperationSpecs),
+    /// Fx3A - PITCH Vx (XO-CHIP)
+    Pitch(OperationSpecs),
+    /// Fx55 - LD [I], Vx
+    StoreRegisters(OperationSpecs),
+    /// Fx65 - LD Vx, [I]
+    LoadRegisters(OperationSpecs),
+    /// Fx75 - LD R, Vx (SUPER-CHIP)
+    StoreFlags(OperationSpecs),
+    /// Fx85 - LD Vx, R (SUPER-CHIP)
+    LoadFlags(OperationSpecs)
+}
+
+///	`opcode` does not encode any known instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeError {
+    pub opcode: u16
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown opcode: {:#06x}", self.opcode)
+    }
+}
+
+///	Decodes `opcode` into its instruction, no chip state is needed
+///
+///	# _Arguments_
+///
+/// * `opcode` - _big-endian instruction word_
+pub fn decode(opcode: u16) -> Result<Instruction, DecodeError> {
+    let specs = OperationSpecs {
+        nibble: (0x000F & opcode) as u8, // operation code (if has one)
+        addr: 0x0FFF & opcode,
+        constant: (0x00FF & opcode) as u8,
+        rx: (0x0F & (opcode >> 8)) as u8,
+        ry: (0x0F & (opcode >> 4)) as u8
+    };
+    let instruction = match opcode >> 12 {
+        0x0 => match opcode {
+            0x00E0 => Instruction::Cls,
+            0x00EE => Instruction::Ret,
+            0x00C0..=0x00CF => Instruction::ScrollDown(specs),
+            0x00FB => Instruction::ScrollRight,
+            0x00FC => Instruction::ScrollLeft,
+            0x00FD => Instruction::Exit,
+            0x00FE => Instruction::LowRes,
+            0x00FF => Instruction::HighRes,
+            0x00A1..=0x00A3 => Instruction::MaskInterrupts(specs),
+            0x00B1..=0x00B3 => Instruction::UnmaskInterrupts(specs),
+            0x0091..=0x0093 => Instruction::StopTimers(specs),
+            0x0081..=0x0083 => Instruction::ResumeTimers(specs),
+            // 00nn stays reserved for the special instructions
+            0x0100..=0x0FFF => Instruction::Sys(specs),
+            _ => return Err(DecodeError { opcode })
+        },
+        0x1 => Instruction::Jump(specs),
+        0x2 => Instruction::Call(specs),
+        0x3 => Instruction::SkipEqualByte(specs),
+        0x4 => Instruction::SkipNotEqualByte(specs),
+        0x5 => match specs.nibble {
+            0x0 => Instruction::SkipEqual(specs),
+            0x2 => Instruction::SaveRange(specs),
+            0x3 => Instruction::LoadRange(specs),
+            _ => return Err(DecodeError { opcode })
+        },
+        0x6 => Instruction::LoadByte(specs),
+        0x7 => Instruction::AddByte(specs),
+        0x8 => match specs.nibble {
+            0x0 => Instruction::Load(specs),
+            0x1 => Instruction::Or(specs),
+            0x2 => Instruction::And(specs),
+            0x3 => Instruction::Xor(specs),
+            0x4 => Instruction::Add(specs),
+            0x5 => Instruction::Sub(specs),
+            0x6 => Instruction::ShiftRight(specs),
+            0x7 => Instruction::SubNegated(specs),
+            0xE => Instruction::ShiftLeft(specs),
+            _ => return Err(DecodeError { opcode })
+        },
+        0x9 => match specs.nibble {
+            0x0 => Instruction::SkipNotEqual(specs),
+            _ => Instruction::DrawHorizontal(specs)
+        },
+        0xA => Instruction::LoadI(specs),
+        0xB => Instruction::JumpOffset(specs),
+        0xC => Instruction::Random(specs),
+        0xD => Instruction::Draw(specs),
+        // constant is placed in the same bit-space as the nibble and ry in E and F operations
+        0xE => match specs.constant {
+            0x9E => Instruction::SkipKeyPressed(specs),
+            0xA1 => Instruction::SkipKeyNotPressed(specs),
+            _ => Instruction::DrawRotated(specs)
+        },
+        _ => match specs.constant {
+            0x00 if specs.rx == 0 => Instruction::LoadLongI,
+            0x01 => Instruction::Plane(specs),
+            0x02 if specs.rx == 0 => Instruction::Audio,
+            0x07 => Instruction::LoadDelayTimer(specs),
+            0x0A => Instruction::WaitKey(specs),
+            0x15 => Instruction::SetDelayTimer(specs),
+            0x18 => Instruction::SetSoundTimer(specs),
+            0x1E => Instruction::AddI(specs),
+            0x29 => Instruction::LoadFont(specs),
+            0x30 => Instruction::LoadBigFont(specs),
+            0x33 => Instruction::StoreBcd(specs),
+            0x3A => Instruction::Pitch(specs),
+            0x55 => Instruction::StoreRegisters(specs),
+            0x65 => Instruction::LoadRegisters(specs),
+            0x75 => Instruction::StoreFlags(specs),
+            0x85 => Instruction::LoadFlags(specs),
+            _ => return Err(DecodeError { opcode })
+        }
+    };
+    Ok(instruction)
+}
+
+impl Instruction {
+    ///	Executes the instruction on `chip`, PC is updated by the instruction itself
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `chip` - _chip the instruction acts on_
+    pub fn execute(self, chip: &mut Chip8) -> Result<(), ChipError> {
+        match self {
+            Instruction::Cls => Cls {}.execute(OperationSpecs::default(), chip),
+            Instruction::Ret => Ret {}.execute(OperationSpecs::default(), chip),
+            Instruction::ScrollDown(specs) => ScrollDown {}.execute(specs, chip),
+            Instruction::ScrollRight => ScrollRight {}.execute(OperationSpecs::default(), chip),
+            Instruction::ScrollLeft => ScrollLeft {}.execute(OperationSpecs::default(), chip),
+            // the end of program is handled by execute_cycle before executing anything
+            Instruction::Exit => Ok(()),
+            Instruction::LowRes => LowRes {}.execute(OperationSpecs::default(), chip),
+            Instruction::HighRes => HighRes {}.execute(OperationSpecs::default(), chip),
+            Instruction::MaskInterrupts(specs) => MaskInterrupts {}.execute(specs, chip),
+            Instruction::UnmaskInterrupts(specs) => UnmaskInterrupts {}.execute(specs, chip),
+            Instruction::StopTimers(specs) => StopTimers {}.execute(specs, chip),
+            Instruction::ResumeTimers(specs) => ResumeTimers {}.execute(specs, chip),
+            // machine code routines of the COSMAC VIP can't run here
+            Instruction::Sys(_) => {
+                chip.update_pc(None);
+                Ok(())
+            },
+            Instruction::DrawRotated(_) | Instruction::DrawHorizontal(_) if !chip.get_config().extended_sprites => {
+                Err(ChipError::ExtensionDisabled { pc: chip.get_pc(), opcode: chip.get_memory_word(chip.get_pc()), extension: "extended_sprites" })
+            },
+            Instruction::Jump(specs) => Opt1 {}.execute(specs, chip),
+            Instruction::Call(specs) => Opt2 {}.execute(specs, chip),
+            Instruction::SkipEqualByte(specs) => Opt3 {}.execute(specs, chip),
+            Instruction::SkipNotEqualByte(specs) => Opt4 {}.execute(specs, chip),
+            Instruction::SkipEqual(specs) => {
+                Opt5 {}.execute_0(specs, chip);
+                Ok(())
+            },
+            Instruction::SaveRange(specs) => {
+                Opt5 {}.execute_2(specs, chip);
+                Ok(())
+            },
+            Instruction::LoadRange(specs) => {
+                Opt5 {}.execute_3(specs, chip);
+                Ok(())
+            },
+            Instruction::LoadByte(specs) => Opt6 {}.execute(specs, chip),
+            Instruction::AddByte(specs) => Opt7 {}.execute(specs, chip),
+            Instruction::Load(specs) => {
+                Opt8 {}.execute_0(specs, chip);
+                Ok(())
+            },
+            Instruction::Or(specs) => {
+                Opt8 {}.execute_1(specs, chip);
+                Ok(())
+            },
+            Instruction::And(specs) => {
+                Opt8 {}.execute_2(specs, chip);
+                Ok(())
+            },
+            Instruction::Xor(specs) => {
+                Opt8 {}.execute_3(specs, chip);
+                Ok(())
+            },
+            Instruction::Add(specs) => {
+                Opt8 {}.execute_4(specs, chip);
+                Ok(())
+            },
+            Instruction::Sub(specs) => {
+                Opt8 {}.execute_5(specs, chip);
+                Ok(())
+            },
+            Instruction::ShiftRight(specs) => {
+                Opt8 {}.execute_6(specs, chip);
+                Ok(())
+            },
+            Instruction::SubNegated(specs) => {
+                Opt8 {}.execute_7(specs, chip);
+                Ok(())
+            },
+            Instruction::ShiftLeft(specs) => {
+                Opt8 {}.execute_e(specs, chip);
+                Ok(())
+            },
+            Instruction::SkipNotEqual(specs) => Opt9 {}.execute(specs, chip),
+            Instruction::DrawHorizontal(specs) => DrawHorizontal {}.execute(specs, chip),
+            Instruction::LoadI(specs) => OptA {}.execute(specs, chip),
+            Instruction::JumpOffset(specs) => OptB {}.execute(specs, chip),
+            Instruction::Random(specs) => OptC {}.execute(specs, chip),
+            Instruction::Draw(specs) => OptD {}.execute(specs, chip),
+            Instruction::DrawRotated(specs) => DrawRotated {}.execute(specs, chip),
+            Instruction::SkipKeyPressed(specs) => {
+                OptE {}.execute_x9e(specs, chip);
+                Ok(())
+            },
+            Instruction::SkipKeyNotPressed(specs) => {
+                OptE {}.execute_xa1(specs, chip);
+                Ok(())
+            },
+            Instruction::LoadLongI => {
+                OptF {}.execute_000(chip);
+                Ok(())
+            },
+            Instruction::Plane(specs) => {
+                OptF {}.execute_n01(specs, chip);
+                Ok(())
+            },
+            Instruction::Audio => {
+                OptF {}.execute_002(chip);
+                Ok(())
+            },
+            Instruction::LoadDelayTimer(specs) => {
+                OptF {}.execute_x07(specs, chip);
+                Ok(())
+            },
+            Instruction::WaitKey(specs) => {
+                OptF {}.execute_x0a(specs, chip);
+                Ok(())
+            },
+            Instruction::SetDelayTimer(specs) => {
+                OptF {}.execute_x15(specs, chip);
+                Ok(())
+            },
+            Instruction::SetSoundTimer(specs) => {
+                OptF {}.execute_x18(specs, chip);
+                Ok(())
+            },
+            Instruction::AddI(specs) => {
+                OptF {}.execute_x1e(specs, chip);
+                Ok(())
+            },
+            Instruction::LoadFont(specs) => {
+                OptF {}.execute_x29(specs, chip);
+                Ok(())
+            },
+            Instruction::LoadBigFont(specs) => {
+                OptF {}.execute_x30(specs, chip);
+                Ok(())
+            },
+            Instruction::StoreBcd(specs) => {
+                OptF {}.execute_x33(specs, chip);
+                Ok(())
+            },
+            Instruction::Pitch(specs) => {
+                OptF {}.execute_x3a(specs, chip);
+                Ok(())
+            },
+            Instruction::StoreRegisters(specs) => {
+                OptF {}.execute_x55(specs, chip);
+                Ok(())
+            },
+            Instruction::LoadRegisters(specs) => {
+                OptF {}.execute_x65(specs, chip);
+                Ok(())
+            },
+            Instruction::StoreFlags(specs) => {
+                OptF {}.execute_x75(specs, chip);
+                Ok(())
+            },
+            Instruction::LoadFlags(specs) => {
+                OptF {}.execute_x85(specs, chip);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Disassembles the instruction with the mnemonics accepted by the assembler
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Instruction::Cls => write!(f, "CLS"),
+            Instruction::Ret => write!(f, "RET"),
+            Instruction::ScrollDown(s) => write!(f, "SCD {}", s.nibble),
+            Instruction::ScrollRight => write!(f, "SCR"),
+            Instruction::ScrollLeft => write!(f, "SCL"),
+            Instruction::Exit => write!(f, "EXIT"),
+            Instruction::LowRes => write!(f, "LOW"),
+            Instruction::HighRes => write!(f, "HIGH"),
+            Instruction::MaskInterrupts(s) => write!(f, "MASK{}", timers_operand(s.nibble)),
+            Instruction::UnmaskInterrupts(s) => write!(f, "UNMASK{}", timers_operand(s.nibble)),
+            Instruction::StopTimers(s) => write!(f, "STOP{}", timers_operand(s.nibble)),
+            Instruction::ResumeTimers(s) => write!(f, "RESUME{}", timers_operand(s.nibble)),
+            Instruction::Sys(s) => write!(f, "SYS {:#05x}", s.addr),
+            Instruction::Jump(s) => write!(f, "JP {:#05x}", s.addr),
+            Instruction::Call(s) => write!(f, "CALL {:#05x}", s.addr),
+            Instruction::SkipEqualByte(s) => write!(f, "SE V{:X}, {:#04x}", s.rx, s.constant),
+            Instruction::SkipNotEqualByte(s) => write!(f, "SNE V{:X}, {:#04x}", s.rx, s.constant),
+            Instruction::SkipEqual(s) => write!(f, "SE V{:X}, V{:X}", s.rx, s.ry),
+            Instruction::SaveRange(s) => write!(f, "SAVE V{:X}, V{:X}", s.rx, s.ry),
+            Instruction::LoadRange(s) => write!(f, "LOAD V{:X}, V{:X}", s.rx, s.ry),
+            Instruction::LoadByte(s) => write!(f, "LD V{:X}, {:#04x}", s.rx, s.constant),
+            Instruction::AddByte(s) => write!(f, "ADD V{:X}, {:#04x}", s.rx, s.constant),
+            Instruction::Load(s) => write!(f, "LD V{:X}, V{:X}", s.rx, s.ry),
+            Instruction::Or(s) => write!(f, "OR V{:X}, V{:X}", s.rx, s.ry),
+            Instruction::And(s) => write!(f, "AND V{:X}, V{:X}", s.rx, s.ry),
+            Instruction::Xor(s) => write!(f, "XOR V{:X}, V{:X}", s.rx, s.ry),
+            Instruction::Add(s) => write!(f, "ADD V{:X}, V{:X}", s.rx, s.ry),
+            Instruction::Sub(s) => write!(f, "SUB V{:X}, V{:X}", s.rx, s.ry),
+            Instruction::ShiftRight(s) => write!(f, "SHR V{:X}, V{:X}", s.rx, s.ry),
+            Instruction::SubNegated(s) => write!(f, "SUBN V{:X}, V{:X}", s.rx, s.ry),
+            Instruction::ShiftLeft(s) => write!(f, "SHL V{:X}, V{:X}", s.rx, s.ry),
+            Instruction::SkipNotEqual(s) => write!(f, "SNE V{:X}, V{:X}", s.rx, s.ry),
+            Instruction::DrawHorizontal(s) => write!(f, "DRWH V{:X}, V{:X}, {}", s.rx, s.ry, s.nibble),
+            Instruction::LoadI(s) => write!(f, "LD I, {:#05x}", s.addr),
+            Instruction::JumpOffset(s) => write!(f, "JP V0, {:#05x}", s.addr),
+            Instruction::Random(s) => write!(f, "RND V{:X}, {:#04x}", s.rx, s.constant),
+            Instruction::Draw(s) => write!(f, "DRW V{:X}, V{:X}, {}", s.rx, s.ry, s.nibble),
+            Instruction::DrawRotated(s) => write!(f, "DRVH V{:X}, V{:X}, V{:X}", s.rx, s.ry, s.nibble),
+            Instruction::SkipKeyPressed(s) => write!(f, "SKP V{:X}", s.rx),
+            Instruction::SkipKeyNotPressed(s) => write!(f, "SKNP V{:X}", s.rx),
+            // the address is the next word, only available in binary ROMs
+            Instruction::LoadLongI => write!(f, "LD I, LONG"),
+            Instruction::Plane(s) => write!(f, "PLANE {}", s.rx),
+            Instruction::Audio => write!(f, "AUDIO"),
+            Instruction::LoadDelayTimer(s) => write!(f, "LD V{:X}, DT", s.rx),
+            Instruction::WaitKey(s) => write!(f, "LD V{:X}, K", s.rx),
+            Instruction::SetDelayTimer(s) => write!(f, "LD DT, V{:X}", s.rx),
+            Instruction::SetSoundTimer(s) => write!(f, "LD ST, V{:X}", s.rx),
+            Instruction::AddI(s) => write!(f, "ADD I, V{:X}", s.rx),
+            Instruction::LoadFont(s) => write!(f, "LD F, V{:X}", s.rx),
+            Instruction::LoadBigFont(s) => write!(f, "LD HF, V{:X}", s.rx),
+            Instruction::StoreBcd(s) => write!(f, "LD B, V{:X}", s.rx),
+            Instruction::Pitch(s) => write!(f, "PITCH V{:X}", s.rx),
+            Instruction::StoreRegisters(s) => write!(f, "LD [I], V{:X}", s.rx),
+            Instruction::LoadRegisters(s) => write!(f, "LD V{:X}, [I]", s.rx),
+            Instruction::StoreFlags(s) => write!(f, "LD R, V{:X}", s.rx),
+            Instruction::LoadFlags(s) => write!(f, "LD V{:X}, R", s.rx)
+        }
+    }
+}
+
+///	Operand of MASK / UNMASK / STOP / RESUME, no operand stands for both timers
+fn timers_operand(mask: u8) -> &'static str {
+    match mask {
+        DELAY_TIMER_IRQ => " DT",
+        SOUND_TIMER_IRQ => " ST",
+        _ => ""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chip8::Chip8;
+    use super::{decode, Instruction, DecodeError};
+
+    #[test]
+    fn decode_test() {
+        assert_eq!(Ok(Instruction::Cls), decode(0x00E0));
+        assert_eq!(Ok(Instruction::Exit), decode(0x00FD));
+        assert_eq!(Err(DecodeError { opcode: 0x0000 }), decode(0x0000));
+        assert_eq!(Err(DecodeError { opcode: 0x8128 }), decode(0x8128));
+        assert!(matches!(decode(0x0123), Ok(Instruction::Sys(specs)) if specs.addr == 0x123));
+        assert!(matches!(decode(0xE1FF), Ok(Instruction::DrawRotated(specs)) if specs.rx == 1 && specs.ry == 0xF && specs.nibble == 0xF));
+        assert!(matches!(decode(0xD123), Ok(Instruction::Draw(specs)) if specs.rx == 1 && specs.ry == 2 && specs.nibble == 3));
+        assert!(matches!(decode(0xF265), Ok(Instruction::LoadRegisters(specs)) if specs.rx == 2));
+        assert_eq!(Ok(Instruction::LoadLongI), decode(0xF000));
+    }
+
+    #[test]
+    fn sys_ignored_test() {
+        let mut chip = Chip8::new();
+        let pc = chip.get_pc();
+        decode(0x0300).unwrap().execute(&mut chip).unwrap();
+        assert_eq!(pc + 2, chip.get_pc());
+    }
+
+    #[test]
+    fn disassemble_test() {
+        let listing: Vec<String> = [0x00E0, 0x6A0F, 0x8124, 0xA300, 0xD125, 0xF233, 0xF301, 0xE13A, 0x0300, 0x00A2, 0x00B3, 0x0091]
+            .iter()
+            .map(|opcode| decode(*opcode).unwrap().to_string())
+            .collect();
+        assert_eq!(listing, vec!["CLS", "LD VA, 0x0f", "ADD V1, V2", "LD I, 0x300", "DRW V1, V2, 5", "LD B, V2", "PLANE 3", "DRVH V1, V3, VA", "SYS 0x300", "MASK ST", "UNMASK", "STOP DT"]);
+    }
+}
@@ -1,10 +1,7 @@
 use crate::chip8::Chip8;
-use super::{opt_1::Opt1, opt_2::Opt2, opt_3::Opt3, 
-    opt_4::Opt4, opt_5::Opt5, opt_6::Opt6, 
-    opt_7::Opt7, opt_8::Opt8, opt_9::Opt9,
-    opt_a::OptA, opt_b::OptB, opt_c::OptC,
-    opt_d::OptD, opt_f::OptF};
+use crate::errors::ChipError;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct OperationSpecs {
     pub nibble: u8,
     pub addr: u16,
@@ -13,53 +10,148 @@ pub struct OperationSpecs {
     pub ry: u8
 }
 
-pub struct OperationTab { }
-impl OperationTab {
-    pub fn fetch_operation(code: u8) -> Option<Box<dyn Executable>> {
-        match code {
-            0 => todo!(),
-            0x10 => Some(Box::new(Opt1 {})),
-            0x20 => Some(Box::new(Opt2 {})),
-            0x30 => Some(Box::new(Opt3 {})),
-            0x40 => Some(Box::new(Opt4 {})),
-            0x50 => Some(Box::new(Opt5 {})),
-            0x60 => Some(Box::new(Opt6 {})),
-            0x70 => Some(Box::new(Opt7 {})),
-            0x80 => Some(Box::new(Opt8 {})),
-            0x90 => Some(Box::new(Opt9 {})),
-            0xA0 => Some(Box::new(OptA {})),
-            0xB0 => Some(Box::new(OptB {})),
-            0xC0 => Some(Box::new(OptC {})),
-            0xD0 => Some(Box::new(OptD {})),
-            0xF0 => Some(Box::new(OptF {})),
-            _ => None
-        }
-    }
-}
-
-
 pub trait Executable {
     // - Execute custom operation
     // - Modify PC
-    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), String>;
+    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError>;
 }
 
 // special instructions
 
 pub struct Ret { }
 pub struct Cls { }
+pub struct ScrollDown { }
+pub struct ScrollRight { }
+pub struct ScrollLeft { }
+pub struct LowRes { }
+pub struct HighRes { }
+pub struct MaskInterrupts { }
+pub struct UnmaskInterrupts { }
+pub struct StopTimers { }
+pub struct ResumeTimers { }
 
 impl Executable for Ret {
-    fn execute(&self, _specs: OperationSpecs, chip: &mut Chip8) -> Result<(), String> {
-        chip.leave_subroutine();
+    fn execute(&self, _specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
+        chip.leave_subroutine()?;
         chip.update_pc(None);
         Ok(())
     }
 }
 impl Executable for Cls {
-    fn execute(&self, _specs: OperationSpecs, chip: &mut Chip8) -> Result<(), String> {
+    fn execute(&self, _specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
         chip.clear_display();
+        chip.set_draw_flag();
+        chip.update_pc(None);
+        Ok(())
+    }
+}
+
+// SUPER-CHIP special instructions
+
+/// 00Cn - SCD nibble
+///
+/// Scroll the display down n pixels.
+impl Executable for ScrollDown {
+    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
+        chip.scroll_display_down(specs.nibble as usize);
+        chip.set_draw_flag();
         chip.update_pc(None);
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// 00FB - SCR
+///
+/// Scroll the display right 4 pixels.
+impl Executable for ScrollRight {
+    fn execute(&self, _specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
+        chip.scroll_display_horizontal(4);
+        chip.set_draw_flag();
+        chip.update_pc(None);
+        Ok(())
+    }
+}
+
+/// 00FC - SCL
+///
+/// Scroll the display left 4 pixels.
+impl Executable for ScrollLeft {
+    fn execute(&self, _specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
+        chip.scroll_display_horizontal(-4);
+        chip.set_draw_flag();
+        chip.update_pc(None);
+        Ok(())
+    }
+}
+
+/// 00FE - LOW
+///
+/// Disable high resolution mode, back to the configured display size.
+impl Executable for LowRes {
+    fn execute(&self, _specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
+        chip.set_high_res(false);
+        chip.set_draw_flag();
+        chip.update_pc(None);
+        Ok(())
+    }
+}
+
+/// 00FF - HIGH
+///
+/// Enable high resolution mode, the display size is doubled (128x64 by default).
+impl Executable for HighRes {
+    fn execute(&self, _specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
+        chip.set_high_res(true);
+        chip.set_draw_flag();
+        chip.update_pc(None);
+        Ok(())
+    }
+}
+
+// interrupt controller instructions
+
+/// 00Am - MASK {DT | ST}
+///
+/// Hold the timer interrupts in the bitmask m pending (bit 0 delay timer, bit 1 sound timer).
+impl Executable for MaskInterrupts {
+    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
+        chip.mask_interrupts(specs.nibble);
+        chip.update_pc(None);
+        Ok(())
+    }
+}
+
+/// 00Bm - UNMASK {DT | ST}
+///
+/// Dispatch again the timer interrupts in the bitmask m, the ones held while masked are dispatched first.
+impl Executable for UnmaskInterrupts {
+    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
+        chip.unmask_interrupts(specs.nibble);
+        chip.update_pc(None);
+        Ok(())
+    }
+}
+
+// timer instructions
+
+/// 009m - STOP {DT | ST}
+///
+/// Freeze the timers in the bitmask m (bit 0 delay timer, bit 1 sound timer), they keep their count.
+impl Executable for StopTimers {
+    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
+        chip.set_timers_paused(specs.nibble, true);
+        chip.update_pc(None);
+        Ok(())
+    }
+}
+
+/// 008m - RESUME {DT | ST}
+///
+/// Restart the timers in the bitmask m from the count they were stopped at.
+impl Executable for ResumeTimers {
+    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
+        chip.set_timers_paused(specs.nibble, false);
+        chip.update_pc(None);
+        Ok(())
+    }
+}
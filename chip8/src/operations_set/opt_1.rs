@@ -1,5 +1,6 @@
 use super::operations_table::*;
 use crate::Chip8;
+use crate::errors::ChipError;
 
 /// 1nnn - JP addr
 /// 
@@ -9,9 +10,9 @@ use crate::Chip8;
  
 pub struct Opt1 {}
 impl Executable for Opt1 {
-    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), String> {
-        if Chip8::address_out_of_bounds(specs.addr) {
-            Err("Address out of bounds in function call: Opt1.execute() ".to_string())
+    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
+        if chip.address_out_of_bounds(specs.addr) {
+            Err(ChipError::AddressOutOfBounds { pc: chip.get_pc(), addr: specs.addr })
         }
         else {
             chip.update_pc(Some(specs.addr));
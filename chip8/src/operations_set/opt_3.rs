@@ -1,5 +1,6 @@
 use super::operations_table::*;
 use crate::Chip8;
+use crate::errors::ChipError;
 
 /// 3xkk - SE Vx, byte
 ///
@@ -8,9 +9,9 @@ use crate::Chip8;
 ///The interpreter compares register Vx to kk, and if they are equal, increments the program counter by 2.
 pub struct Opt3 {}
 impl Executable for Opt3 {
-    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), String> {
+    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
         if chip.get_register_value(specs.rx) == specs.constant {
-            chip.update_pc(None);
+            chip.skip_next_instruction();
         }
         chip.update_pc(None);
         Ok(())    
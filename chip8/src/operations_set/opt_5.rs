@@ -1,18 +1,86 @@
 use super::operations_table::*;
 use crate::Chip8;
 
-/// 5xy0 - SE Vx, Vy
-/// Skip next instruction if Vx = Vy.
-/// 
-/// The interpreter compares register Vx to register Vy, and if they are equal, increments the program counter by 2.
-
 pub struct Opt5 {}
-impl Executable for Opt5 {
-    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), String> {
+impl Opt5 {
+    /// 5xy0 - SE Vx, Vy
+    /// Skip next instruction if Vx = Vy.
+    ///
+    /// The interpreter compares register Vx to register Vy, and if they are equal, increments the program counter by 2.
+    pub(crate) fn execute_0(&self, specs: OperationSpecs, chip: &mut Chip8) {
         if chip.get_register_value(specs.rx) == chip.get_register_value(specs.ry) {
-            chip.update_pc(None);
+            chip.skip_next_instruction();
+        }
+        chip.update_pc(None);
+    }
+
+    /// 5xy2 - SAVE Vx, Vy (XO-CHIP)
+    ///
+    /// Store registers Vx through Vy in memory starting at location I.
+    ///
+    /// The range may go in either direction (x > y stores them in reverse order). I is not modified.
+    pub(crate) fn execute_2(&self, specs: OperationSpecs, chip: &mut Chip8) {
+        for (offset, r) in Opt5::register_range(specs.rx, specs.ry).enumerate() {
+            chip.set_memory_value(offset, chip.get_register_value(r));
+        }
+        chip.update_pc(None);
+    }
+
+    /// 5xy3 - LOAD Vx, Vy (XO-CHIP)
+    ///
+    /// Read registers Vx through Vy from memory starting at location I.
+    ///
+    /// The range may go in either direction (x > y loads them in reverse order). I is not modified.
+    pub(crate) fn execute_3(&self, specs: OperationSpecs, chip: &mut Chip8) {
+        for (offset, r) in Opt5::register_range(specs.rx, specs.ry).enumerate() {
+            let value = chip.load_i_address_value(offset);
+            chip.set_register_value(r, value);
         }
         chip.update_pc(None);
-        Ok(())    
     }
-}
\ No newline at end of file
+
+    fn register_range(rx: u8, ry: u8) -> Box<dyn Iterator<Item = u8>> {
+        if rx <= ry {
+            Box::new(rx..=ry)
+        } else {
+            Box::new((ry..=rx).rev())
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{operations_set::operations_table::OperationSpecs, chip8::Chip8};
+    use super::Opt5;
+
+    #[test]
+    fn execute_2_3_test() {
+        let save_specs = OperationSpecs {
+            nibble: 0x02,
+            addr: 0x0312,
+            constant: 0x12,
+            rx: 0x03,
+            ry: 0x01
+        };
+        let load_specs = OperationSpecs {
+            nibble: 0x03,
+            addr: 0x0573,
+            constant: 0x73,
+            rx: 0x05,
+            ry: 0x07
+        };
+        let mut chip = Chip8::new();
+        chip.set_register_value(0x01, 0x11);
+        chip.set_register_value(0x02, 0x22);
+        chip.set_register_value(0x03, 0x33);
+        chip.set_i_register_value(0x400);
+        // saved in reverse order
+        Opt5::execute_2(&Opt5 {  }, save_specs, &mut chip);
+        assert_eq!([0x33, 0x22, 0x11], (0..3).map(|i| chip.load_i_address_value(i)).collect::<Vec<u8>>()[..]);
+        Opt5::execute_3(&Opt5 {  }, load_specs, &mut chip);
+        assert_eq!(0x33, chip.get_register_value(0x05));
+        assert_eq!(0x11, chip.get_register_value(0x07));
+        assert_eq!(0x400, chip.get_i_register_value());
+    }
+}
@@ -1,5 +1,6 @@
 use super::operations_table::*;
 use crate::Chip8;
+use crate::errors::ChipError;
 
 /// 6xkk - LD Vx, byte
 /// Set Vx = kk.
@@ -7,7 +8,7 @@ use crate::Chip8;
 /// The interpreter puts the value kk into register Vx.
 pub struct Opt6 {}
 impl Executable for Opt6 {
-    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), String> {
+    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
         chip.set_register_value(specs.rx, specs.constant);
         chip.update_pc(None);
         Ok(())    
@@ -1,5 +1,6 @@
 use super::operations_table::*;
 use crate::Chip8;
+use crate::errors::ChipError;
 
 /// 7xkk - ADD Vx, byte
 /// Set Vx = Vx + kk.
@@ -7,9 +8,9 @@ use crate::Chip8;
 /// Adds the value kk to the value of register Vx, then stores the result in Vx.
 pub struct Opt7 {}
 impl Executable for Opt7 {
-    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), String> {
+    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
         let rx_value = chip.get_register_value(specs.rx);
-        chip.set_register_value(specs.rx, specs.constant + rx_value);
+        chip.set_register_value(specs.rx, rx_value.wrapping_add(specs.constant));
         chip.update_pc(None);
         Ok(())    
     }
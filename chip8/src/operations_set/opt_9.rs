@@ -1,5 +1,6 @@
 use super::operations_table::*;
 use crate::Chip8;
+use crate::errors::ChipError;
 
 /// 9xy0 - SNE Vx, Vy
 ///
@@ -8,9 +9,9 @@ use crate::Chip8;
 ///The values of Vx and Vy are compared, and if they are not equal, the program counter is increased by 2.
 pub struct Opt9 {}
 impl Executable for Opt9 {
-    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), String> {
+    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
         if chip.get_register_value(specs.rx) != chip.get_register_value(specs.ry) {
-            chip.update_pc(None);
+            chip.skip_next_instruction();
         }
         chip.update_pc(None);
         Ok(())    
@@ -1,5 +1,6 @@
 use super::operations_table::*;
 use crate::Chip8;
+use crate::errors::ChipError;
 
 /// Annn - LD I, addr
 /// 
@@ -8,7 +9,7 @@ use crate::Chip8;
 /// The value of register I is set to nnn.
 pub struct OptA {}
 impl Executable for OptA {
-    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), String> {
+    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
         chip.set_i_register_value(specs.addr);
         chip.update_pc(None);
         Ok(())
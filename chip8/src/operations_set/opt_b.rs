@@ -1,18 +1,22 @@
 use super::operations_table::*;
 use crate::Chip8;
+use crate::errors::ChipError;
 
 /// Bnnn - JP V0, addr
 /// 
 /// Jump to location nnn + V0.
 /// 
 /// The program counter is set to nnn plus the value of V0. It only uses V0, if other register is specified, it will still use V0
+///
+/// With the `jump_uses_vx` quirk it behaves as Bxnn - JP Vx, addr and jumps to xnn plus the value of Vx.
 pub struct OptB {}
 impl Executable for OptB {
-    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), String> {
-        let r0 = chip.get_register_value(0);
-        let eff_addr = specs.addr+r0 as u16;
-        if Chip8::address_out_of_bounds(eff_addr) {
-            Err("Address out of bounds in function call: OptB.execute() ".to_string())
+    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
+        let offset_reg = if chip.get_quirks().jump_uses_vx { specs.rx } else { 0 };
+        let offset = chip.get_register_value(offset_reg);
+        let eff_addr = specs.addr+offset as u16;
+        if chip.address_out_of_bounds(eff_addr) {
+            Err(ChipError::AddressOutOfBounds { pc: chip.get_pc(), addr: eff_addr })
         }
         else {
             chip.update_pc(Some(eff_addr));
@@ -1,7 +1,6 @@
 use super::operations_table::*;
 use crate::Chip8;
-use rand;
-
+use crate::errors::ChipError;
 
 /// Cxkk - RND Vx, byte
 /// 
@@ -9,11 +8,14 @@ use rand;
 /// 
 /// The interpreter generates a random number from 0 to 255, which is then ANDed with the value kk. 
 /// The results are stored in Vx. See instruction 8xy2 for more information on AND.
+///
+/// The random numbers come from the chip's seedable generator, so replays draw the same ones.
 pub struct OptC {}
 impl Executable for OptC {
-    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), String> {
-        let rand_no = rand::random::<u8>();
+    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
+        let rand_no = chip.random_byte();
         chip.set_register_value(specs.rx, rand_no & specs.constant);
+        chip.update_pc(None);
         Ok(())
     }
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 use super::operations_table::*;
-use crate::Chip8;
+use crate::{Chip8, chip8::VfFlag};
+use crate::errors::ChipError;
 
 /// Dxyn - DRW Vx, Vy, nibble
 ///
@@ -14,23 +15,95 @@ use crate::Chip8;
 /// If the sprite is positioned so part of it is outside the coordinates of the display, it wraps around to the opposite side of the screen. 
 ///
 /// See instruction 8xy3 for more information on XOR, and section 2.4, Display, for more information on the Chip-8 screen and sprites.
+///
+/// Dxy0 (SUPER-CHIP) draws a 16x16 sprite made of 32 bytes, two per row.
+///
+/// With several XO-CHIP planes selected, the sprite data for each plane follows the previous one in memory.
 pub struct OptD {}
 
 
 impl Executable for OptD {
-    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), String> {
+    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
 
         // take coordinates values
         let coord = (chip.get_register_value(specs.rx), chip.get_register_value(specs.ry));
-        for i in 0..specs.nibble as usize {
-            let sprite = chip.load_i_address_value(i);
-            chip.set_gfx_sprite(coord, i, sprite);
+        let mut collision = false;
+        // XO-CHIP: each selected plane takes its own sprite data, one after the other starting at I
+        for (index, plane) in chip.get_selected_planes().into_iter().enumerate() {
+            if specs.nibble == 0 {
+                // Dxy0 - SUPER-CHIP 16x16 sprite, two bytes per row
+                let base = index * 32;
+                for i in 0..16 {
+                    let sprite = (chip.load_i_address_value(base + 2*i) as u16) << 8 | chip.load_i_address_value(base + 2*i+1) as u16;
+                    collision |= chip.set_gfx_plane_sprite(plane, coord, i, sprite, 16);
+                }
+            } else {
+                let base = index * specs.nibble as usize;
+                for i in 0..specs.nibble as usize {
+                    let sprite = chip.load_i_address_value(base + i);
+                    collision |= chip.set_gfx_plane_sprite(plane, coord, i, sprite as u16, 8);
+                }
+            }
         }
+        chip.set_vf_flag(VfFlag::Collision, collision);
         
         // activate draw_flag
-        let mut flag_reg = chip.get_register_value(0x0F);
-        flag_reg = flag_reg | 0x80;
-        chip.set_register_value(0x0F, flag_reg);
+        chip.set_draw_flag();
+        chip.update_pc(None);
+        Ok(())
+    }
+}
+
+/// 9xyn - DRWH Vx, Vy, nibble
+///
+/// Display n-byte sprite starting at memory location I at (Vx, Vy) applied horizontally, set VF = collision.
+///
+/// Each byte is a column of 8 pixels with the MSB at the top, so the sprite is n pixels wide and 8 pixels tall
+/// (the DRW sprite rotated). 9xy0 is still SNE, only available with `extended_sprites`.
+pub struct DrawHorizontal {}
+
+impl Executable for DrawHorizontal {
+    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
+        let coord = (chip.get_register_value(specs.rx), chip.get_register_value(specs.ry));
+        let columns = specs.nibble as usize;
+        let mut collision = false;
+        for (index, plane) in chip.get_selected_planes().into_iter().enumerate() {
+            let sprite: Vec<u8> = (0..columns).map(|i| chip.load_i_address_value(index * columns + i)).collect();
+            for row in 0..8 {
+                // transpose: bit `row` of every byte, leftmost column is the first byte
+                let sprite_row = sprite.iter().fold(0_u16, |acc, byte| acc << 1 | ((byte << row) & 0x80) as u16 >> 7);
+                collision |= chip.set_gfx_plane_sprite(plane, coord, row, sprite_row, columns);
+            }
+        }
+        chip.set_vf_flag(VfFlag::Collision, collision);
+        chip.set_draw_flag();
+        chip.update_pc(None);
+        Ok(())
+    }
+}
+
+/// Exyn - DRVH Vx, Vy, Vn
+///
+/// Display the 8-byte sprite starting at memory location I at (Vx, Vy) rotated by Vn = 0xVH, set VF = collision.
+///
+/// The rows are rotated V (Vn >> 4) rows down and every row is rotated H (Vn & 0x0F) pixels right, both wrap inside the sprite.
+/// Ex9E and ExA1 are still SKP and SKNP, so Vy, Vn can't be V9, VE nor VA, V1. Only available with `extended_sprites`.
+pub struct DrawRotated {}
+
+impl Executable for DrawRotated {
+    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), ChipError> {
+        let coord = (chip.get_register_value(specs.rx), chip.get_register_value(specs.ry));
+        let rotation = chip.get_register_value(specs.nibble);
+        let (vertical, horizontal) = ((rotation >> 4) as usize, (rotation & 0x0F) as u32);
+        let mut collision = false;
+        for (index, plane) in chip.get_selected_planes().into_iter().enumerate() {
+            for row in 0..8 {
+                let sprite_row = chip.load_i_address_value(index * 8 + (row + 8 - vertical % 8) % 8).rotate_right(horizontal);
+                collision |= chip.set_gfx_plane_sprite(plane, coord, row, sprite_row as u16, 8);
+            }
+        }
+        chip.set_vf_flag(VfFlag::Collision, collision);
+        chip.set_draw_flag();
         chip.update_pc(None);
         Ok(())
     }
@@ -38,7 +111,8 @@ impl Executable for OptD {
 
 #[cfg(test)]
 mod tests {
-    use crate::{operations_set::operations_table::OperationSpecs, chip8::Chip8};
+    use crate::{operations_set::operations_table::{OperationSpecs, Executable}, chip8::Chip8};
+    use super::{OptD, DrawHorizontal, DrawRotated};
 
     
     #[test]
@@ -52,12 +126,68 @@ mod tests {
             ry: 0x02
         };
         let mut chip = Chip8::new();
-        // (0,0) coordinates
+        // (10, 30) coordinates, Vx is the column and Vy the row
+        chip.set_register_value(0x01, 10);
+        chip.set_register_value(0x02, 30);
+        chip.set_i_register_value(0x600);
+        [0xFF, 0x81, 0x18].iter().enumerate().for_each(|(i, b)| chip.set_memory_value(i, *b));
+        OptD::execute(&OptD {  }, specs, &mut chip).unwrap();
+        assert_eq!(chip.get_register_value(0x0F), 0);
+        let gfx = chip.get_gfx();
+        assert_eq!(gfx.row_pixels(30).skip(10).take(8).filter(|lit| *lit).count(), 8);
+        assert!(gfx.get_pixel(10, 31) && gfx.get_pixel(17, 31) && !gfx.get_pixel(11, 31));
+        // the third row wraps around to the top
+        assert!(gfx.get_pixel(13, 0) && gfx.get_pixel(14, 0));
+        assert_eq!(chip.get_gfx_sprite((10, 31), 1), 0x18);
+
+        // a single erased pixel is a collision
+        chip.set_i_register_value(0x603);
+        chip.set_memory_value(0, 0x20);
+        chip.set_register_value(0x02, 0);
+        let single_row = OperationSpecs { nibble: 0x01, ..specs };
+        OptD::execute(&OptD {  }, single_row, &mut chip).unwrap();
+        assert_eq!(chip.get_register_value(0x0F), 0);
+        chip.set_register_value(0x01, 11);
+        OptD::execute(&OptD {  }, single_row, &mut chip).unwrap();
+        assert_eq!(chip.get_register_value(0x0F), 1);
+        assert!(!chip.get_gfx().get_pixel(13, 0));
+    }
+
+    #[test]
+    fn draw_horizontal_and_rotated() {
+        // DRWH V1, V2, 2
+        let horizontal_specs = OperationSpecs {
+            nibble: 0x02,
+            addr: 0x0122,
+            constant: 0x22,
+            rx: 0x01,
+            ry: 0x02
+        };
+        // DRVH V1, V2, V3
+        let rotated_specs = OperationSpecs {
+            nibble: 0x03,
+            addr: 0x0123,
+            constant: 0x23,
+            rx: 0x01,
+            ry: 0x02
+        };
+        let mut chip = Chip8::new();
         chip.set_register_value(0x01, 0x00);
         chip.set_register_value(0x02, 0x00);
         chip.set_i_register_value(0x600);
-        // Three store operations for the nibbles
-        // TODO
-        //OptD::execute(&OptD {  }, specs, &mut chip).unwrap();
+        // two full columns
+        chip.set_memory_value(0, 0xFF);
+        chip.set_memory_value(1, 0xFF);
+        DrawHorizontal::execute(&DrawHorizontal {  }, horizontal_specs, &mut chip).unwrap();
+        assert!((0..8).all(|y| chip.get_gfx()[y][0] == 0xC0));
+        assert_eq!(chip.get_gfx()[8][0], 0x00);
+
+        chip.clear_display();
+        // one row down, one pixel right
+        chip.set_register_value(0x03, 0x11);
+        (0..8).for_each(|i| chip.set_memory_value(i, if i == 0 { 0x01 } else { 0x00 }));
+        DrawRotated::execute(&DrawRotated {  }, rotated_specs, &mut chip).unwrap();
+        assert_eq!(chip.get_gfx()[0][0], 0x00);
+        assert_eq!(chip.get_gfx()[1][0], 0x80);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,63 @@
+use super::operations_table::*;
+use crate::Chip8;
+pub struct OptE {}
+impl OptE {
+    /// Ex9E - SKP Vx
+    ///
+    /// Skip next instruction if key with the value of Vx is pressed.
+    ///
+    /// Checks the keyboard, and if the key corresponding to the value of Vx is currently in the down position, PC is increased by 2.
+    pub(crate) fn execute_x9e(&self, specs: OperationSpecs, chip: &mut Chip8) {
+        if chip.is_key_pressed(chip.get_register_value(specs.rx)) {
+            chip.skip_next_instruction();
+        }
+        chip.update_pc(None);
+    }
+
+    /// ExA1 - SKNP Vx
+    ///
+    /// Skip next instruction if key with the value of Vx is not pressed.
+    ///
+    /// Checks the keyboard, and if the key corresponding to the value of Vx is currently in the up position, PC is increased by 2.
+    pub(crate) fn execute_xa1(&self, specs: OperationSpecs, chip: &mut Chip8) {
+        if !chip.is_key_pressed(chip.get_register_value(specs.rx)) {
+            chip.skip_next_instruction();
+        }
+        chip.update_pc(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{operations_set::operations_table::OperationSpecs, chip8::Chip8};
+    use super::OptE;
+
+    #[test]
+    fn execute_x9e_xa1_test() {
+        let specs = OperationSpecs {
+            nibble: 0x0E,
+            addr: 0x019E,
+            constant: 0x9E,
+            rx: 0x01,
+            ry: 0x09
+        };
+        let mut chip = Chip8::new();
+        let pc = chip.get_pc();
+        chip.set_register_value(0x01, 0x07);
+        // key up, SKP doesn't skip and SKNP does
+        OptE::execute_x9e(&OptE {  }, specs, &mut chip);
+        assert_eq!(pc + 2, chip.get_pc());
+        OptE::execute_xa1(&OptE {  }, specs, &mut chip);
+        assert_eq!(pc + 6, chip.get_pc());
+        // key down, the other way around
+        chip.press_key(0x07);
+        OptE::execute_x9e(&OptE {  }, specs, &mut chip);
+        assert_eq!(pc + 10, chip.get_pc());
+        OptE::execute_xa1(&OptE {  }, specs, &mut chip);
+        assert_eq!(pc + 12, chip.get_pc());
+        // only the key in Vx counts
+        chip.set_register_value(0x01, 0x08);
+        OptE::execute_x9e(&OptE {  }, specs, &mut chip);
+        assert_eq!(pc + 14, chip.get_pc());
+    }
+}
@@ -1,24 +1,71 @@
 use super::operations_table::*;
-use crate::{Chip8, chip8::RoutinePurpose};
+use crate::{Chip8, chip8::{RoutinePurpose, RPL_FLAGS}};
 pub struct OptF {}
 impl OptF {
-    pub fn select_execution(&self, specs: OperationSpecs, chip: &mut Chip8) -> Option<()> {
-        // constant is placed in the same bit-space as the nibble and ry in F operations
-        match specs.constant {
-            0x15 => Some(self.execute_x15(specs, chip)),
-            0x18 => Some(self.execute_x18(specs, chip)),
-            0x55 => Some(self.execute_x55(specs, chip)),
-            // ...
-            _ => None// invalid nibble
+    /// F000 NNNN - LD I, NNNN (XO-CHIP)
+    ///
+    /// Set I = NNNN, the 16-bit word following the instruction.
+    ///
+    /// This is the only 4 bytes long instruction, the PC skips both words.
+    pub(crate) fn execute_000(&self, chip: &mut Chip8) {
+        match chip.read_memory_word(chip.get_pc() as usize + 2) {
+            Ok(addr) => {
+                chip.set_i_register_value(addr);
+                chip.update_pc(Some(chip.get_pc() + 4));
+            },
+            Err(fault) => chip.raise_fault(fault)
         }
     }
+
+    /// Fn01 - PLANE n (XO-CHIP)
+    ///
+    /// Select the drawing planes given by the bitmask n.
+    ///
+    /// 0 selects no plane, 1 the first, 2 the second and 3 both. Drawing, clearing and scrolling only affect the selected planes.
+    pub(crate) fn execute_n01(&self, specs: OperationSpecs, chip: &mut Chip8) {
+        chip.select_planes(specs.rx);
+        chip.update_pc(None);
+    }
+
+    /// F002 - AUDIO (XO-CHIP)
+    ///
+    /// Load the 16 bytes audio pattern starting at location I.
+    pub(crate) fn execute_002(&self, chip: &mut Chip8) {
+        chip.load_audio_pattern();
+        chip.update_pc(None);
+    }
     
+    /// Fx07 - LD Vx, DT
+    ///
+    /// Set Vx = delay timer value.
+    ///
+    /// The value of DT is placed into Vx.
+    pub(crate) fn execute_x07(&self, specs: OperationSpecs, chip: &mut Chip8) {
+        let dt = chip.get_delay_timer_value();
+        chip.set_register_value(specs.rx, dt);
+        chip.update_pc(None);
+    }
+
+    /// Fx0A - LD Vx, K
+    ///
+    /// Wait for a key press, store the value of the key in Vx.
+    ///
+    /// All execution stops until a key is pressed, then the value of that key is stored in Vx.
+    /// As on the COSMAC VIP the key has to be pressed and released, a key still held from a previous Fx0A doesn't count.
+    /// While waiting the PC is not updated, so the instruction is fetched again on the next cycle.
+    pub(crate) fn execute_x0a(&self, specs: OperationSpecs, chip: &mut Chip8) {
+        if let Some(key) = chip.wait_key_release() {
+            chip.set_register_value(specs.rx, key);
+            chip.update_pc(None);
+        }
+    }
+
     /// Fx15 - LD DT, Vx
     ///
     /// Set delay timer = Vx.
     ///
     /// DT is set equal to the value of Vx.
-    fn execute_x15(&self, specs: OperationSpecs, chip: &mut Chip8) { 
+    pub(crate) fn execute_x15(&self, specs: OperationSpecs, chip: &mut Chip8) { 
         let rx = chip.get_register_value(specs.rx);
         let delay_timer_addr = chip.get_routine_addr(RoutinePurpose::DelayTimer);
         chip.set_delay_timer(rx, delay_timer_addr);
@@ -30,29 +77,207 @@ impl OptF {
     /// Set sound timer = Vx.
     ///
     /// ST is set equal to the value of Vx.
-    fn execute_x18(&self, specs: OperationSpecs, chip: &mut Chip8) { 
+    pub(crate) fn execute_x18(&self, specs: OperationSpecs, chip: &mut Chip8) { 
         let rx = chip.get_register_value(specs.rx);
         let sound_timer_addr = chip.get_routine_addr(RoutinePurpose::SoundTimer);
         chip.set_sound_timer(rx, sound_timer_addr);
         chip.update_pc(None);
     }
 
+    /// Fx1E - ADD I, Vx
+    ///
+    /// Set I = I + Vx.
+    ///
+    /// The values of I and Vx are added, and the results are stored in I.
+    pub(crate) fn execute_x1e(&self, specs: OperationSpecs, chip: &mut Chip8) {
+        let rx = chip.get_register_value(specs.rx);
+        chip.set_i_register_value(chip.get_i_register_value().wrapping_add(rx as u16));
+        chip.update_pc(None);
+    }
+
+    /// Fx29 - LD F, Vx
+    ///
+    /// Set I = location of sprite for digit Vx.
+    ///
+    /// The value of I is set to the location for the hexadecimal sprite corresponding to the value of Vx.
+    pub(crate) fn execute_x29(&self, specs: OperationSpecs, chip: &mut Chip8) {
+        let rx = chip.get_register_value(specs.rx);
+        chip.set_i_register_value(chip.get_font_sprite_addr(rx));
+        chip.update_pc(None);
+    }
+
+    /// Fx30 - LD HF, Vx
+    ///
+    /// Set I = location of the SUPER-CHIP large sprite for digit Vx.
+    ///
+    /// The value of I is set to the location for the 8x10 hexadecimal sprite corresponding to the value of Vx.
+    pub(crate) fn execute_x30(&self, specs: OperationSpecs, chip: &mut Chip8) {
+        let rx = chip.get_register_value(specs.rx);
+        chip.set_i_register_value(chip.get_big_font_sprite_addr(rx));
+        chip.update_pc(None);
+    }
+
+    /// Fx33 - LD B, Vx
+    ///
+    /// Store BCD representation of Vx in memory locations I, I+1, and I+2.
+    ///
+    /// The interpreter takes the decimal value of Vx, and places the hundreds digit in memory at location in I,
+    /// the tens digit at location I+1, and the ones digit at location I+2.
+    pub(crate) fn execute_x33(&self, specs: OperationSpecs, chip: &mut Chip8) {
+        let rx = chip.get_register_value(specs.rx);
+        chip.set_memory_value(0, rx / 100);
+        chip.set_memory_value(1, (rx / 10) % 10);
+        chip.set_memory_value(2, rx % 10);
+        chip.update_pc(None);
+    }
+
     /// Fx55 - LD [I], Vx
     /// 
     ///Store registers V0 through Vx in memory starting at location I.
     ///
     ///The interpreter copies the values of registers V0 through Vx into memory, starting at the address in I.
-    fn execute_x55(&self, specs: OperationSpecs, chip: &mut Chip8) {
-        (0..specs.rx+1).into_iter().for_each(|r| chip.set_memory_value(chip.get_register_value(r)));
+    ///The `load_store_increment` quirk decides whether I is left untouched, at I + x or at I + x + 1.
+    pub(crate) fn execute_x55(&self, specs: OperationSpecs, chip: &mut Chip8) {
+        (0..specs.rx+1).into_iter().for_each(|r| chip.set_memory_value(r as usize, chip.get_register_value(r)));
+        let increment = chip.get_quirks().load_store_increment.offset(specs.rx);
+        chip.set_i_register_value(chip.get_i_register_value().wrapping_add(increment));
+        chip.update_pc(None);
+    }
+
+    /// Fx65 - LD Vx, [I]
+    ///
+    /// Read registers V0 through Vx from memory starting at location I.
+    ///
+    /// The interpreter reads values from memory starting at location I into registers V0 through Vx.
+    /// The `load_store_increment` quirk decides whether I is left untouched, at I + x or at I + x + 1.
+    pub(crate) fn execute_x65(&self, specs: OperationSpecs, chip: &mut Chip8) {
+        (0..specs.rx+1).into_iter().for_each(|r| {
+            let value = chip.load_i_address_value(r as usize);
+            chip.set_register_value(r, value);
+        });
+        let increment = chip.get_quirks().load_store_increment.offset(specs.rx);
+        chip.set_i_register_value(chip.get_i_register_value().wrapping_add(increment));
+        chip.update_pc(None);
+    }
+
+    /// Fx3A - PITCH Vx (XO-CHIP)
+    ///
+    /// Set the audio pattern playback rate to 4000*2^((Vx-64)/48) bits per second.
+    pub(crate) fn execute_x3a(&self, specs: OperationSpecs, chip: &mut Chip8) {
+        chip.set_pitch(chip.get_register_value(specs.rx));
+        chip.update_pc(None);
+    }
+
+    /// Fx75 - LD R, Vx
+    ///
+    /// Store V0 through Vx in the SUPER-CHIP RPL user flags.
+    ///
+    /// There are 8 flags, x is capped at 7.
+    pub(crate) fn execute_x75(&self, specs: OperationSpecs, chip: &mut Chip8) {
+        (0..=specs.rx.min(RPL_FLAGS as u8 - 1)).for_each(|r| chip.set_rpl_flag(r as usize, chip.get_register_value(r)));
+        chip.update_pc(None);
+    }
+
+    /// Fx85 - LD Vx, R
+    ///
+    /// Read V0 through Vx from the SUPER-CHIP RPL user flags.
+    ///
+    /// There are 8 flags, x is capped at 7.
+    pub(crate) fn execute_x85(&self, specs: OperationSpecs, chip: &mut Chip8) {
+        (0..=specs.rx.min(RPL_FLAGS as u8 - 1)).for_each(|r| chip.set_register_value(r, chip.get_rpl_flag(r as usize)));
         chip.update_pc(None);
     }
 }
 
-impl Executable for OptF {
-    fn execute(&self, specs: OperationSpecs, chip: &mut Chip8) -> Result<(), String> {
-        match self.select_execution(specs, chip) {
-            Some(()) => Ok(()),
-            None => Err("OptF execution failed: ".to_string())
-        }
+
+#[cfg(test)]
+mod tests {
+    use crate::{operations_set::operations_table::OperationSpecs, chip8::Chip8};
+    use super::OptF;
+
+    #[test]
+    fn execute_x29_test() {
+        let specs = OperationSpecs {
+            nibble: 0x09,
+            addr: 0x0129,
+            constant: 0x29,
+            rx: 0x01,
+            ry: 0x02
+        };
+        let mut chip = Chip8::new();
+        chip.set_register_value(0x01, 0x0A);
+        OptF::execute_x29(&OptF {  }, specs, &mut chip);
+        assert_eq!(chip.get_font_sprite_addr(0x0A), chip.get_i_register_value());
+        // sprite for A
+        assert_eq!([0xF0, 0x90, 0xF0, 0x90, 0x90], (0..5).map(|i| chip.load_i_address_value(i)).collect::<Vec<u8>>()[..]);
+    }
+
+    #[test]
+    fn execute_x33_x65_test() {
+        let bcd_specs = OperationSpecs {
+            nibble: 0x03,
+            addr: 0x0133,
+            constant: 0x33,
+            rx: 0x01,
+            ry: 0x03
+        };
+        let load_specs = OperationSpecs {
+            nibble: 0x05,
+            addr: 0x0265,
+            constant: 0x65,
+            rx: 0x02,
+            ry: 0x06
+        };
+        let mut chip = Chip8::new();
+        chip.set_register_value(0x01, 254);
+        chip.set_i_register_value(0x400);
+        OptF::execute_x33(&OptF {  }, bcd_specs, &mut chip);
+        OptF::execute_x65(&OptF {  }, load_specs, &mut chip);
+        assert_eq!(2, chip.get_register_value(0x00));
+        assert_eq!(5, chip.get_register_value(0x01));
+        assert_eq!(4, chip.get_register_value(0x02));
+    }
+
+    #[test]
+    fn execute_x0a_test() {
+        let specs = OperationSpecs {
+            nibble: 0x0A,
+            addr: 0x030A,
+            constant: 0x0A,
+            rx: 0x03,
+            ry: 0x00
+        };
+        let mut chip = Chip8::new();
+        let pc = chip.get_pc();
+        // a key held from before has to be pressed again
+        chip.press_key(0x04);
+        OptF::execute_x0a(&OptF {  }, specs, &mut chip);
+        chip.release_key(0x04);
+        OptF::execute_x0a(&OptF {  }, specs, &mut chip);
+        assert_eq!(pc, chip.get_pc());
+        // blocks until the key is released
+        chip.press_key(0x0B);
+        OptF::execute_x0a(&OptF {  }, specs, &mut chip);
+        assert_eq!(pc, chip.get_pc());
+        chip.release_key(0x0B);
+        OptF::execute_x0a(&OptF {  }, specs, &mut chip);
+        assert_eq!(pc + 2, chip.get_pc());
+        assert_eq!(0x0B, chip.get_register_value(0x03));
+        // the next wait starts over
+        chip.press_key(0x0C);
+        OptF::execute_x0a(&OptF {  }, specs, &mut chip);
+        assert_eq!(pc + 2, chip.get_pc());
+    }
+
+    #[test]
+    fn execute_000_test() {
+        let mut chip = Chip8::new();
+        let pc = chip.get_pc();
+        // place F000 1234 at PC
+        chip.set_i_register_value(pc);
+        [0xF0, 0x00, 0x12, 0x34].iter().enumerate().for_each(|(i, b)| chip.set_memory_value(i, *b));
+        OptF::execute_000(&OptF {  }, &mut chip);
+        assert_eq!(0x1234, chip.get_i_register_value());
+        assert_eq!(pc + 4, chip.get_pc());
     }
 }
@@ -0,0 +1,69 @@
+use serde::{Serialize, Deserialize};
+
+/// End of the historical interpreter area, protected by default
+pub const INTERPRETER_AREA_END: usize = 0x200;
+
+///	What programs are allowed to do with a protected region
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Protection {
+    /// programs can read and execute it, but not write it
+    ReadOnly,
+    /// programs can read and write it, but the PC can't enter it
+    NoExec,
+    /// only the interpreter writes it (registers, stack), programs can just read it, e.g. the font sprites
+    InterpreterOnly
+}
+
+///	Kind of access a program makes to memory
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryAccess {
+    Write,
+    Execute
+}
+
+///	Memory range from `start` up to, not including, `end`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryRegion {
+    pub start: usize,
+    pub end: usize,
+    pub protection: Protection
+}
+
+impl MemoryRegion {
+    ///	Checks whether this region forbids `access` at `addr`
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `addr` - _accessed address_
+    /// * `access` - _kind of access_
+    pub fn forbids(&self, addr: usize, access: MemoryAccess) -> bool {
+        if addr < self.start || addr >= self.end {
+            return false;
+        }
+        matches!((self.protection, access),
+            (Protection::ReadOnly, MemoryAccess::Write) | (Protection::NoExec, MemoryAccess::Execute) | (Protection::InterpreterOnly, _))
+    }
+}
+
+///	Regions protected unless configured otherwise: the interpreter area holding registers, stack and fonts
+pub fn default_regions() -> Vec<MemoryRegion> {
+    vec![MemoryRegion { start: 0, end: INTERPRETER_AREA_END, protection: Protection::InterpreterOnly }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MemoryRegion, Protection, MemoryAccess};
+
+    #[test]
+    fn region_access() {
+        let rom = MemoryRegion { start: 0x200, end: 0x300, protection: Protection::ReadOnly };
+        assert!(rom.forbids(0x200, MemoryAccess::Write));
+        assert!(!rom.forbids(0x2FF, MemoryAccess::Execute));
+        assert!(!rom.forbids(0x300, MemoryAccess::Write));
+        let data = MemoryRegion { start: 0x800, end: 0x900, protection: Protection::NoExec };
+        assert!(data.forbids(0x800, MemoryAccess::Execute));
+        assert!(!data.forbids(0x800, MemoryAccess::Write));
+        let interpreter = MemoryRegion { start: 0, end: 0x200, protection: Protection::InterpreterOnly };
+        assert!(interpreter.forbids(0x1FF, MemoryAccess::Write) && interpreter.forbids(0x000, MemoryAccess::Execute));
+    }
+}
@@ -0,0 +1,96 @@
+use serde::{Serialize, Deserialize};
+
+///	Behaviours that differ between well-known CHIP-8 interpreters
+/// The default matches no historical interpreter in particular: shifts work in place, Fx55/Fx65 leave I untouched,
+/// Bnnn jumps relative to V0 and sprites wrap around the screen
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Quirks {
+    /// 8xy6/8xyE store Vy shifted into Vx instead of shifting Vx in place
+    pub shift_uses_vy: bool,
+    /// where Fx55/Fx65 leave I
+    pub load_store_increment: LoadStoreIncrement,
+    /// Bxnn jumps to xnn + Vx instead of nnn + V0
+    pub jump_uses_vx: bool,
+    /// sprites are cut at the screen edges instead of wrapping to the opposite side, the starting position (Vx, Vy) always wraps
+    pub clip_sprites: bool
+}
+
+///	Value of I after Fx55/Fx65 stored or loaded V0 through Vx
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoadStoreIncrement {
+    /// I is not modified (SUPER-CHIP)
+    #[default]
+    Unchanged,
+    /// I is left at I + x, pointing at the last register (CHIP-48)
+    PlusX,
+    /// I is left at I + x + 1, right after the last register (COSMAC VIP)
+    PlusXPlusOne
+}
+
+impl LoadStoreIncrement {
+    ///	Amount added to I by an Fx55/Fx65 with register `x`
+    pub fn offset(&self, x: u8) -> u16 {
+        match self {
+            LoadStoreIncrement::Unchanged => 0,
+            LoadStoreIncrement::PlusX => x as u16,
+            LoadStoreIncrement::PlusXPlusOne => x as u16 + 1
+        }
+    }
+}
+
+/// Named quirks profiles
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum QuirksPreset {
+    CosmacVip,
+    Chip48,
+    SuperChip
+}
+
+impl Quirks {
+    pub fn from_preset(preset: QuirksPreset) -> Self {
+        match preset {
+            QuirksPreset::CosmacVip => Quirks {
+                shift_uses_vy: true,
+                load_store_increment: LoadStoreIncrement::PlusXPlusOne,
+                jump_uses_vx: false,
+                clip_sprites: true
+            },
+            QuirksPreset::Chip48 => Quirks {
+                shift_uses_vy: false,
+                load_store_increment: LoadStoreIncrement::PlusX,
+                jump_uses_vx: true,
+                clip_sprites: true
+            },
+            QuirksPreset::SuperChip => Quirks {
+                shift_uses_vy: false,
+                load_store_increment: LoadStoreIncrement::Unchanged,
+                jump_uses_vx: true,
+                clip_sprites: true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Quirks, QuirksPreset, LoadStoreIncrement};
+
+    #[test]
+    fn presets() {
+        let vip = Quirks::from_preset(QuirksPreset::CosmacVip);
+        assert!(vip.shift_uses_vy && !vip.jump_uses_vx);
+        assert_eq!(vip.load_store_increment.offset(3), 4);
+        let chip48 = Quirks::from_preset(QuirksPreset::Chip48);
+        assert!(!chip48.shift_uses_vy && chip48.jump_uses_vx);
+        assert_eq!(chip48.load_store_increment.offset(3), 3);
+        let schip = Quirks::from_preset(QuirksPreset::SuperChip);
+        assert!(!schip.shift_uses_vy && schip.jump_uses_vx);
+        assert_eq!(schip.load_store_increment.offset(3), 0);
+        assert_eq!(Quirks::default(), Quirks {
+            shift_uses_vy: false,
+            load_store_increment: LoadStoreIncrement::Unchanged,
+            jump_uses_vx: false,
+            clip_sprites: false
+        });
+    }
+}
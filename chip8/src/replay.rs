@@ -0,0 +1,93 @@
+use std::fs;
+use serde::{Serialize, Deserialize};
+
+use crate::errors::ChipError;
+use crate::timers::TimerMode;
+
+/// Version of the replay format written by `Replay::save`, bumped whenever a field changes
+pub const REPLAY_VERSION: u32 = 2;
+
+///	A key changing state right before executing cycle number `cycle`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub cycle: u64,
+    pub key: u8,
+    pub pressed: bool
+}
+
+///	Keypad input of a session, together with the seed of the random number generator
+/// Sessions are recorded and replayed with `TimerMode::Cycles`, threaded timers depend on the host
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Replay {
+    pub version: u32,
+    /// the generator is reseeded with it when recording starts
+    pub seed: u64,
+    /// cycle the recording started at, the cycle counter is set back to it when the replay starts
+    pub start_cycle: u64,
+    /// timer mode used while recording
+    pub timer_mode: TimerMode,
+    /// in cycle order
+    pub events: Vec<KeyEvent>
+}
+
+impl Replay {
+    ///	Creates an empty replay recorded with `TimerMode::Cycles`
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `seed` - _seed of the random number generator when recording starts_
+    /// * `start_cycle` - _cycle the recording starts at_
+    pub fn new(seed: u64, start_cycle: u64) -> Self {
+        Replay {
+            version: REPLAY_VERSION,
+            seed,
+            start_cycle,
+            timer_mode: TimerMode::Cycles,
+            events: Vec::new()
+        }
+    }
+
+    ///	Writes the replay to `file`
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `file` - _path of the replay file, overwritten if it exists_
+    pub fn save(&self, file: &str) -> Result<(), ChipError> {
+        let text = serde_yaml::to_string(self).map_err(|e| ChipError::Replay(e.to_string()))?;
+        fs::write(file, text).map_err(|e| ChipError::Io(e.to_string()))
+    }
+
+    ///	Reads a replay written by `save`, rejecting other format versions
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `file` - _path of the replay file_
+    pub fn load(file: &str) -> Result<Replay, ChipError> {
+        let text = fs::read_to_string(file).map_err(|e| ChipError::Io(e.to_string()))?;
+        let replay: Replay = serde_yaml::from_str(&text).map_err(|e| ChipError::Replay(e.to_string()))?;
+        if replay.version != REPLAY_VERSION {
+            return Err(ChipError::Replay(format!("version {} is not supported, expected {}", replay.version, REPLAY_VERSION)));
+        }
+        Ok(replay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Replay, KeyEvent};
+    use crate::errors::ChipError;
+
+    #[test]
+    fn save_and_load() {
+        let mut replay = Replay::new(0xC8, 3);
+        replay.events.push(KeyEvent { cycle: 12, key: 0x5, pressed: true });
+        replay.events.push(KeyEvent { cycle: 40, key: 0x5, pressed: false });
+        let file = std::env::temp_dir().join("chip8_replay_test.yaml");
+        let file = file.to_str().unwrap();
+        replay.save(file).unwrap();
+        assert_eq!(Replay::load(file).unwrap(), replay);
+        replay.version += 1;
+        replay.save(file).unwrap();
+        assert!(matches!(Replay::load(file), Err(ChipError::Replay(_))));
+    }
+}
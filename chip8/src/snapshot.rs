@@ -0,0 +1,93 @@
+use std::fs;
+use serde::{Serialize, Deserialize};
+
+use crate::chip8::{ChipConfig, RoutineParams, RPL_FLAGS, AUDIO_PATTERN_SIZE, MAX_MEMORY_SIZE};
+use crate::errors::ChipError;
+use crate::framebuffer::Framebuffer;
+use crate::interrupts::InterruptController;
+use crate::timers::TimerState;
+
+/// Version of the snapshot format written by `Snapshot::save`, bumped whenever a field changes
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+///	Whole state of a chip, registers and stack included since they are memory mapped
+/// The keypad is left out, keys are whatever is held when the snapshot is restored
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Snapshot {
+    pub version: u32,
+    pub(crate) memory: Vec<u8>,
+    pub(crate) i_register: u16,
+    pub(crate) pc: u16,
+    pub(crate) sp: u8,
+    pub(crate) delay_timer: Option<TimerState>,
+    pub(crate) sound_timer: Option<TimerState>,
+    pub(crate) frame_cycles: u32,
+    pub(crate) gfx: Framebuffer,
+    pub(crate) gfx_second_plane: Framebuffer,
+    pub(crate) selected_planes: u8,
+    pub(crate) high_res: bool,
+    pub(crate) rpl_flags: [u8; RPL_FLAGS],
+    pub(crate) audio_pattern: [u8; AUDIO_PATTERN_SIZE],
+    pub(crate) pitch: u8,
+    pub(crate) routines: Vec<RoutineParams>,
+    pub(crate) interrupts: InterruptController,
+    pub(crate) config: ChipConfig
+}
+
+impl Snapshot {
+    ///	Writes the snapshot to `file`
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `file` - _path of the state file, overwritten if it exists_
+    pub fn save(&self, file: &str) -> Result<(), ChipError> {
+        let text = serde_yaml::to_string(self).map_err(|e| ChipError::Snapshot(e.to_string()))?;
+        fs::write(file, text).map_err(|e| ChipError::Io(e.to_string()))
+    }
+
+    ///	Reads a snapshot written by `save`, rejecting other format versions
+    ///
+    ///	# _Arguments_
+    ///
+    /// * `file` - _path of the state file_
+    pub fn load(file: &str) -> Result<Snapshot, ChipError> {
+        let text = fs::read_to_string(file).map_err(|e| ChipError::Io(e.to_string()))?;
+        let snapshot: Snapshot = serde_yaml::from_str(&text).map_err(|e| ChipError::Snapshot(e.to_string()))?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(ChipError::Snapshot(format!("version {} is not supported, expected {}", snapshot.version, SNAPSHOT_VERSION)));
+        }
+        Ok(snapshot)
+    }
+
+    ///	Checks the state can be restored without faulting the interpreter later, hand-edited files included
+    pub fn validate(&self) -> Result<(), ChipError> {
+        let invalid = |cause: String| Err(ChipError::Snapshot(cause));
+        let config = &self.config;
+        if self.version != SNAPSHOT_VERSION {
+            return invalid(format!("version {} is not supported, expected {}", self.version, SNAPSHOT_VERSION));
+        }
+        if config.memory_size > MAX_MEMORY_SIZE || self.memory.len() != config.memory_size {
+            return invalid(format!("{} bytes of memory saved, config says {}", self.memory.len(), config.memory_size));
+        }
+        if let Some(cause) = config.stack_region_error() {
+            return invalid(cause);
+        }
+        if config.font_region_collides() {
+            return invalid(format!("font sprites at {:#06x} overlap registers or stack or don't fit in memory", config.font_addr));
+        }
+        if self.sp as usize > config.stack_depth() {
+            return invalid(format!("stack pointer {} is deeper than the {} entries of the stack", self.sp, config.stack_depth()));
+        }
+        if self.pc as usize + 1 >= config.memory_size {
+            return invalid(format!("program counter {:#06x} is past the end of memory", self.pc));
+        }
+        let scale = if self.high_res { 2 } else { 1 };
+        let display_size = (config.display_width * scale, config.display_height * scale);
+        for plane in [&self.gfx, &self.gfx_second_plane] {
+            if (plane.width(), plane.height()) != display_size {
+                return invalid(format!("{}x{} display saved, config says {}x{}", plane.width(), plane.height(), display_size.0, display_size.1));
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use std::{thread, sync::{Arc, /*Mutex,*/ mpsc::{self, Sender, TryRecvError}}};
 use std::time::Duration;
 use parking_lot::Mutex;
+use serde::{Serialize, Deserialize};
 
 /// Unix-like signals to be sent accross threads (specially for timers)
 pub enum Signals {
@@ -9,10 +10,21 @@ pub enum Signals {
     RES // resume thread
 }
 
+/// How the delay and sound timers count down
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TimerMode {
+    /// every timer runs on its own thread sleeping 1/60 s between decrements
+    Threaded,
+    /// timers are decremented by the emulation loop every `instructions_per_frame` cycles, identical inputs give identical runs
+    Cycles
+}
+
 ///	Stores the time left for a launched timer and a pointer to the routine to handle the end of the countdown
 #[derive(Debug)]
 pub struct TimerThread {
     pub timer: u8,
+    /// a paused timer keeps its count until resumed
+    pub paused: bool,
     // holds the direction a subroutine to handle the timer event
     // it can either be custom-made or by default it should send some kind of signal to a pause-like instruction
     pub rti: u16
@@ -29,6 +41,7 @@ impl TimerThread {
     pub fn launch(count: u8, rti: u16) -> (Arc<Mutex<Self>>, Sender<Signals>) {
         let new_timer = Arc::new(Mutex::new(TimerThread { 
             timer: count, 
+            paused: false,
             rti
         }));
         let new_timer_clone = Arc::clone(&new_timer);
@@ -57,6 +70,9 @@ impl TimerThread {
                     Err(TryRecvError::Empty) => {}
                 }
                 let mut t = new_timer_clone.lock(); //.unwrap();
+                if t.paused {
+                    continue;
+                }
                 t.timer -= 1;
                 if t.timer == 0 {
                     break;
@@ -65,6 +81,44 @@ impl TimerThread {
         }).unwrap();
         (new_timer, tx)
     }
+
+    ///	Instantiates a timer without a thread, it only counts down through `tick`
+    ///
+    ///	# __Arguments__
+    ///
+    /// * `count` - _count to set the timer to_
+    /// * `rti` - _address where to handle the interruption_
+    pub fn paced(count: u8, rti: u16) -> Arc<Mutex<Self>> {
+        Arc::new(Mutex::new(TimerThread { 
+            timer: count, 
+            paused: false,
+            rti
+        }))
+    }
+
+    ///	Decrements the count once (one 60Hz period) unless paused, stopping at 0
+    pub fn tick(&mut self) {
+        if !self.paused {
+            self.timer = self.timer.saturating_sub(1);
+        }
+    }
+
+    ///	Copies the count, pause and routine of the timer, so it can be rebuilt later
+    pub fn state(&self) -> TimerState {
+        TimerState {
+            count: self.timer,
+            paused: self.paused,
+            rti: self.rti
+        }
+    }
+}
+
+///	Plain copy of a timer, without the thread counting it down
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct TimerState {
+    pub count: u8,
+    pub paused: bool,
+    pub rti: u16
 }
 
 
@@ -87,6 +141,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn timer_paced() {
+        let timer = TimerThread::paced(2, RTI_DEFAULT_ADDR);
+        let mut timer_lck = timer.lock();
+        timer_lck.tick();
+        assert_eq!(timer_lck.timer, 1);
+        timer_lck.tick();
+        timer_lck.tick();
+        assert_eq!(timer_lck.timer, 0);
+        // no thread holds the timer
+        drop(timer_lck);
+        assert_eq!(Arc::strong_count(&timer), 1);
+    }
+
     #[test]
     fn timer_kill() {
         let (timer, s) = TimerThread::launch(10, RTI_DEFAULT_ADDR);
@@ -3,6 +3,7 @@ use tui::{
     widgets::{Paragraph, Borders, BorderType, Block}, 
     style::{Color, Style}
 };
+use chip8::framebuffer::Framebuffer;
 
 
 pub struct ScreenComponent {
@@ -25,5 +26,27 @@ impl ScreenComponent {
             style: screen
         }
     }
-}
 
+    /// Draws the framebuffer two pixel rows per line with half block characters
+    pub fn update_component(&mut self, gfx: &Framebuffer) {
+        let lines: Vec<String> = (0..gfx.height()).step_by(2).map(|y| {
+            let lower: Vec<bool> = if y + 1 < gfx.height() { gfx.row_pixels(y + 1).collect() } else { vec![false; gfx.width()] };
+            gfx.row_pixels(y).zip(lower).map(|pixels| match pixels {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' '
+            }).collect()
+        }).collect();
+        self.style = Paragraph::new(lines.join("\n"))
+            .style(Style::default().fg(Color::LightCyan))
+            .alignment(Alignment::Left)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .style(Style::default().fg(Color::White))
+                    .title("Output")
+                    .border_type(BorderType::Plain),
+            );
+    }
+}
@@ -4,14 +4,29 @@ use tui::{
     style::{Color, Style}
 };
 use std::fs;
+use chip8::operations_set::instruction::decode;
 pub struct TextComponent {
     pub style: Paragraph<'static>,
     pub text: String
 }
 
 impl TextComponent {
-    pub fn new(file: &str) -> Self {
-        let text = fs::read_to_string(file).unwrap();
+    pub fn new(file: &str, binary: bool) -> Self {
+        // binary ROMs are disassembled one opcode per line so lines still match instructions
+        let text = if binary {
+            fs::read(file).unwrap()
+                .chunks(2)
+                .map(|op| (op[0] as u16) << 8 | *op.get(1).unwrap_or(&0) as u16)
+                .map(|opcode| match decode(opcode) {
+                    Ok(instruction) => format!("{:#06x}  {}", opcode, instruction),
+                    // sprite data or anything else that is not code
+                    Err(_) => format!("{:#06x}", opcode)
+                })
+                .collect::<Vec<String>>()
+                .join("\n")
+        } else {
+            fs::read_to_string(file).unwrap()
+        };
         let code = Paragraph::new(text.clone())
             .style(Style::default().fg(Color::LightCyan))
             .alignment(Alignment::Left)
@@ -1,6 +1,6 @@
 use std::sync::{Arc, mpsc::TryRecvError};
 
-use chip8::{self, chip8::{Chip8, ProgramType}, timers::Signals};
+use chip8::{self, chip8::{Chip8, ProgramType}, errors::ChipError, timers::Signals};
 use crate::display::Display;
 use crate::components::
     timers::{DelayTimerComponent, SoundTimerComponent};
@@ -12,11 +12,12 @@ pub struct Debugger {
 }
 
 impl Debugger {
-    pub fn new(program: &str) -> Self {
+    pub fn new(program: &str, binary: bool) -> Self {
         let mut chip = Chip8::new();
-        chip.load_program(ProgramType::Main(program), None, None).unwrap();
+        let kind = if binary { ProgramType::Binary(program) } else { ProgramType::Main(program) };
+        chip.load_program(kind, None, None).unwrap();
         Self { 
-            display: Display::new(program),
+            display: Display::new(program, binary),
             chip,
             next_breakpoint: None,
             current_line: 0
@@ -32,10 +33,11 @@ impl Debugger {
     }
 
     fn next_instruction_sets_timer(&self) -> Option<&str> {
-        if let Some(_) = self.display.text.text.lines().collect::<Vec<_>>()[self.current_line as usize].find("ST") {
+        // only LD ST, Vx and LD DT, Vx set a timer, LD Vx, DT just reads it
+        if let Some(_) = self.display.text.text.lines().collect::<Vec<_>>()[self.current_line as usize].find("LD ST,") {
             Some("sound")
         }
-        else if let Some(_) = self.display.text.text.lines().collect::<Vec<_>>()[self.current_line as usize].find("DT") {
+        else if let Some(_) = self.display.text.text.lines().collect::<Vec<_>>()[self.current_line as usize].find("LD DT,") {
             Some("delay")
         }
         else {
@@ -67,17 +69,15 @@ impl Debugger {
                     }
                 }
                 if let Err(eop) = self.chip.execute_cycle() {
-                    return Err("Program terminated with status: ".to_string() + &eop.status.to_string())
+                    return Err(match eop {
+                        ChipError::NormalExit { .. } => "Program terminated with status: 0".to_string(),
+                        fault => format!("Program terminated with status: {}, {}", fault.status(), fault)
+                    })
                 }
                 
                 // check if you need to display gfx on screen
-                let vf = self.chip.get_register_value(15);
-                if vf & 0x80 == 0x80 {
-                    // update screen
-                    //update_screen(chip.get_gfx());
-        
-                    // put the draw flag down
-                    self.chip.set_register_value(15, vf & 0x7F);
+                if self.chip.take_draw_flag() {
+                    self.display.screen.update_component(self.chip.get_gfx());
                 }
                 // update current_line
                 //  this is incorrect due to jumps
@@ -29,7 +29,7 @@ pub struct Display {
 }
 impl Display {
 
-    pub fn new(file: &str) -> Self {
+    pub fn new(file: &str, binary: bool) -> Self {
         let stdout = io::stdout();
         let backend = CrosstermBackend::new(stdout);
         let mut terminal = Terminal::new(backend).unwrap();
@@ -40,7 +40,7 @@ impl Display {
             distribution: None, 
             chip_status: RegistersComponent::new(), 
             screen: ScreenComponent::new(),
-            text: TextComponent::new(file),
+            text: TextComponent::new(file, binary),
             command: CommandComponent::new(),
             delay_timer: None,
             sound_timer: None
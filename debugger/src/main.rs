@@ -4,17 +4,18 @@ mod scaffold;
 mod display;
 use crossterm::terminal::{enable_raw_mode, disable_raw_mode};
 use debugger::Debugger;
-use std::env;
+use chip8::config::Args;
+use clap::Parser;
 
 
 
 fn main () {
 
-    let file: Vec<String> = env::args().collect();
+    let args = Args::parse();
     enable_raw_mode().unwrap();
     // crossterm::execute!(io::stdout().lock(), EnterAlternateScreen, EnableMouseCapture).unwrap();
     // initialize debugger
-    let mut debugger = Debugger::new(file[1].as_str());
+    let mut debugger = Debugger::new(args.file.as_str(), args.binary);
     let mut next_cmd;
     debugger.update_screen();
     